@@ -0,0 +1,122 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::{get_samples, wrap_samples};
+use crate::lfo::{Lfo, Waveform};
+use crate::time::resolve_hz_or_note;
+use crate::imaging::{pan_gains, PanLaw};
+
+pub fn autopan(
+    input_wav: &[u8],
+    rate_hz: f32,        // LFO rate in Hz
+    width: f32,          // Sweep width (0.0 stays centred, 1.0 swings hard left to hard right)
+    waveform: Waveform,  // LFO shape
+) -> Result<Vec<u8>, String> {
+    if !(0.0..=1.0).contains(&width) {
+        return Err("Width must be between 0.0 and 1.0.".to_string());
+    }
+
+    let (samples, mut spec) = get_samples(input_wav)?;
+    let channels = spec.channels as usize;
+    if channels > 2 {
+        return Err("Auto-pan only supports mono or stereo input.".to_string());
+    }
+
+    let mut lfo = Lfo::new(waveform, rate_hz, spec.sample_rate as f32);
+
+    // Mono input is spread to stereo so there is something to pan
+    let frames = samples.len() / channels;
+    let mut output = Vec::with_capacity(frames * 2);
+
+    for frame in samples.chunks(channels) {
+        let (left, right) = (frame[0], frame[channels - 1]);
+
+        // The equal-power law of `pan`: -3 dB per side in the centre, constant
+        // power across the sweep and unity on the near side at the edges
+        let (left_gain, right_gain) = pan_gains(lfo.next_value() * width, PanLaw::EqualPower);
+        output.push(left * left_gain);
+        output.push(right * right_gain);
+    }
+
+    spec.channels = 2;
+    wrap_samples(output, spec)
+}
+
+#[wasm_bindgen]
+pub fn autopan_js(
+    input_wav: &[u8],
+    rate: &str,
+    bpm: Option<f32>,
+    width: f32,
+    waveform: &str,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = resolve_hz_or_note(rate, bpm).and_then(|rate_hz| {
+        let waveform = waveform.parse::<Waveform>()?;
+        autopan(input_wav, rate_hz, width, waveform)
+    });
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Sweeps a WAV file across the stereo field", long_about = None)]
+pub struct AutopanArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// LFO rate in Hz (e.g., 0.5) or note division per cycle with --bpm (e.g., 1/2, 1/4d)
+    #[arg(long, default_value = "0.5")]
+    pub rate: String,
+
+    /// Tempo in BPM, needed when the rate is a note division
+    #[arg(long)]
+    pub bpm: Option<f32>,
+
+    /// Sweep width (0.0 to 1.0)
+    #[arg(long, default_value_t = 1.0)]
+    pub width: f32,
+
+    /// LFO waveform (sine, triangle, square, saw, random)
+    #[arg(long, default_value = "sine")]
+    pub waveform: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+    use hound::{WavSpec, SampleFormat};
+
+    #[test]
+    fn test_autopan_effect() {
+        let input_wav = get_dummy();
+
+        let output_wav = autopan(&input_wav, 2.0, 1.0, Waveform::Triangle).expect("autopan function failed");
+
+        assert_eq!(len(&input_wav).unwrap(), len(&output_wav).unwrap(), "Auto-pan should not change the duration");
+        assert_ne!(input_wav, output_wav, "Auto-pan should modify the audio content");
+    }
+
+    #[test]
+    fn test_autopan_mono_becomes_stereo() {
+        let spec = WavSpec { channels: 1, sample_rate: 8, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        let input_wav = wrap_samples(vec![1.0; 8], spec).unwrap();
+
+        // A square LFO at 1 Hz holds hard right for the first half second
+        let output_wav = autopan(&input_wav, 1.0, 1.0, Waveform::Square).unwrap();
+        let (output, output_spec) = get_samples(&output_wav).unwrap();
+
+        assert_eq!(output_spec.channels, 2, "Mono input should be panned to stereo");
+        assert!(output[0].abs() < 1e-6 && (output[1] - 1.0).abs() < 1e-6, "Panned hard right: {:?}", &output[..2]);
+        assert!(output.iter().all(|s| s.abs() <= 1.0), "Full-scale input should not be boosted past 0 dB");
+        let power = |frame: &[f32]| frame[0] * frame[0] + frame[1] * frame[1];
+        assert!(output.chunks(2).all(|frame| (power(frame) - 1.0).abs() < 1e-5), "Equal power across the sweep");
+    }
+}
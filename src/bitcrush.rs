@@ -1,86 +1,86 @@
 use wasm_bindgen::prelude::*;
-use js_sys;
 use clap::Parser;
-use crate::utils::{get_samples,wrap_samples, clamp_samples};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::utils::{get_samples, wrap_samples};
+use crate::filter::OnePoleLowpass;
 
-pub fn bitcrush(input_wav: &[u8], mut semitones: f32) -> Result<Vec<u8>, String> {
-
-    semitones += 24.0;
-
-    let factor = 2.0f32.powf(semitones / 12.0);
-
-    if factor <= 0.0 {
-        return Err("Pitch factor must be positive.".to_string());
+pub fn bitcrush(
+    input_wav: &[u8],
+    bits: f32,        // Bit depth, fractional values allowed (1.0 to 16.0)
+    downsample: f32,  // Sample-and-hold factor (1.0 keeps the original rate)
+    prefilter: bool,  // Lowpass before decimating to tame aliasing
+    dither: bool,     // Add TPDF dither before quantizing
+) -> Result<Vec<u8>, String> {
+    if !(1.0..=16.0).contains(&bits) {
+        return Err("Bit depth must be between 1 and 16.".to_string());
+    }
+    if downsample < 1.0 {
+        return Err("Downsample factor must be at least 1.".to_string());
     }
 
-    let (samples,spec) = get_samples(input_wav)?;
+    let (samples, spec) = get_samples(input_wav)?;
+    let sample_rate = spec.sample_rate as f32;
     let channels = spec.channels as usize;
 
-    let num_input_frames = samples.len() / channels;
-    let num_output_frames_speed_alg = (num_input_frames as f32 / factor) as usize;
-
-    let mut speed_alg_output_samples = Vec::with_capacity(num_output_frames_speed_alg * channels);
+    // Quantization steps per unit of amplitude
+    let levels = 2.0f32.powf(bits - 1.0);
+    let mut rng = StdRng::seed_from_u64(0);
 
-    for i in 0..num_output_frames_speed_alg {
-        for ch in 0..channels {
-            let original_pos = i as f32 * factor;
-            let index1 = original_pos.floor() as usize;
-            let index2 = original_pos.ceil() as usize;
-            let frac = original_pos.fract();
+    // Two cascaded one-poles at the decimated Nyquist frequency
+    let cutoff = sample_rate / (2.0 * downsample);
+    let mut filters: Vec<[OnePoleLowpass; 2]> = (0..channels)
+        .map(|_| [OnePoleLowpass::new(cutoff, sample_rate), OnePoleLowpass::new(cutoff, sample_rate)])
+        .collect();
 
-            let sample1_idx = index1 * channels + ch;
-            let sample2_idx = index2 * channels + ch;
+    let mut held = vec![0.0f32; channels];
+    let mut hold_phase = downsample; // Capture on the very first frame
+    let mut output = Vec::with_capacity(samples.len());
 
-            let sample1 = samples.get(sample1_idx).cloned().unwrap_or(0.0);
-            let sample2 = samples.get(sample2_idx).cloned().unwrap_or(0.0);
-
-            let new_sample = sample1 * (1.0 - frac) + sample2 * frac;
-            speed_alg_output_samples.push(new_sample);
+    for frame in samples.chunks(channels) {
+        let capture = hold_phase >= downsample;
+        if capture {
+            hold_phase -= downsample;
         }
-    }
-
-    // New approach for stuttering/removal
-    let mut final_output_samples = Vec::with_capacity(num_input_frames * channels);
-    let target_len = num_input_frames * channels;
-    let source_len = speed_alg_output_samples.len();
-
-    if target_len == source_len {
-        final_output_samples = speed_alg_output_samples;
-    } else {
-        let ratio = source_len as f32 / target_len as f32; // Ratio of source to target
-
-        for i in 0..target_len {
-            let source_pos = i as f32 * ratio;
-            let index1 = source_pos.floor() as usize;
-            let index2 = source_pos.ceil() as usize;
-            let frac = source_pos.fract();
-
-            let sample1 = speed_alg_output_samples.get(index1).cloned().unwrap_or(0.0);
-            let sample2 = speed_alg_output_samples.get(index2).cloned().unwrap_or(sample1); // Use sample1 if index2 is out of bounds
-
-            let new_sample = sample1 * (1.0 - frac) + sample2 * frac;
-            final_output_samples.push(new_sample);
+        hold_phase += 1.0;
+
+        for (ch, &sample) in frame.iter().enumerate() {
+            let mut x = sample;
+            if prefilter && downsample > 1.0 {
+                x = filters[ch][0].process(x);
+                x = filters[ch][1].process(x);
+            }
+            if capture {
+                held[ch] = x;
+            }
+
+            let mut y = held[ch];
+            if dither {
+                y += (rng.gen_range(0.0..1.0) - rng.gen_range(0.0..1.0)) / levels;
+            }
+            output.push(((y * levels).round() / levels).clamp(-1.0, 1.0));
         }
     }
 
-
-    clamp_samples(&mut final_output_samples);
-    wrap_samples(final_output_samples, spec)
+    wrap_samples(output, spec)
 }
 
 #[wasm_bindgen]
 pub fn bitcrush_js(
     input_wav: &[u8],
-    semitones: f32,
+    bits: f32,
+    downsample: f32,
+    prefilter: bool,
+    dither: bool,
 ) -> Result<js_sys::Uint8Array, JsValue> {
-    match bitcrush(input_wav, semitones) {
+    match bitcrush(input_wav, bits, downsample, prefilter, dither) {
         Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
         Err(e) => Err(JsValue::from_str(&e)),
     }
 }
 
 #[derive(Parser, Debug)]
-#[command(about = "Applies bitcrush effect to a WAV file", long_about = None)]
+#[command(about = "Reduces the bit depth and sample rate of a WAV file", long_about = None)]
 pub struct BitcrushArgs {
     /// Input WAV file
     #[arg()]
@@ -90,9 +90,21 @@ pub struct BitcrushArgs {
     #[arg()]
     pub output: String,
 
-    /// Pitch shift in semitones (e.g., 12 for one octave up, -12 for one octave down)
-    #[arg(allow_hyphen_values = true)]
-    pub semitones: f32,
+    /// Bit depth (1 to 16, fractional values allowed)
+    #[arg()]
+    pub bits: f32,
+
+    /// Sample-and-hold downsampling factor (1 keeps the original rate)
+    #[arg(default_value_t = 1.0)]
+    pub downsample: f32,
+
+    /// Lowpass before downsampling
+    #[arg(long)]
+    pub prefilter: bool,
+
+    /// Add TPDF dither before quantizing
+    #[arg(long)]
+    pub dither: bool,
 }
 
 #[cfg(test)]
@@ -102,19 +114,44 @@ mod tests {
     use crate::len::len;
 
     #[test]
-    fn test_pitch_output_properties() {
+    fn test_bitcrush_quantizes() {
         let input_wav_bytes = get_dummy();
-        let semitones = 2.0; // Example shift in semitones
+        let bits = 3.0;
 
-        let output_wav_bytes = bitcrush(&input_wav_bytes, semitones)
+        let output_wav_bytes = bitcrush(&input_wav_bytes, bits, 1.0, false, false)
             .expect("bitcrush function failed");
 
-        // Check that the output has the same duration using the len function
         let input_duration = len(&input_wav_bytes).expect("Failed to get input duration");
         let output_duration = len(&output_wav_bytes).expect("Failed to get output duration");
         assert_eq!(input_duration, output_duration, "Output WAV duration should be the same as input WAV duration");
 
-        // Check that the content has changed
+        // Every sample should sit on the quantization grid
+        let (samples, _) = get_samples(&output_wav_bytes).unwrap();
+        let levels = 2.0f32.powf(bits - 1.0);
+        for sample in samples {
+            assert!(((sample * levels).round() - sample * levels).abs() < 1e-4, "Sample {} is not quantized", sample);
+        }
+    }
+
+    #[test]
+    fn test_bitcrush_sample_and_hold() {
+        let input_wav_bytes = get_dummy();
+
+        let output_wav_bytes = bitcrush(&input_wav_bytes, 16.0, 4.0, true, true)
+            .expect("bitcrush function failed");
+
+        // Frames come in runs of four identical values, apart from dither
+        let (samples, spec) = get_samples(&output_wav_bytes).unwrap();
+        let channels = spec.channels as usize;
+        let frame = |i: usize| samples[i * channels];
+        assert!((frame(4) - frame(5)).abs() < 1e-3 && (frame(4) - frame(7)).abs() < 1e-3, "Held frames should repeat");
         assert_ne!(input_wav_bytes, output_wav_bytes, "Output WAV content should be different from input WAV content");
     }
+
+    #[test]
+    fn test_bitcrush_rejects_invalid_bits() {
+        let input_wav_bytes = get_dummy();
+        assert!(bitcrush(&input_wav_bytes, 0.5, 1.0, false, false).is_err(), "Bit depth below 1 should be rejected");
+        assert!(bitcrush(&input_wav_bytes, 24.0, 1.0, false, false).is_err(), "Bit depth above 16 should be rejected");
+    }
 }
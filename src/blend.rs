@@ -1,12 +1,15 @@
-use rand::seq::IteratorRandom;
+use rand::seq::{IteratorRandom, SliceRandom};
 use rand::rngs::StdRng;
 use std::collections::HashMap;
 use clap::Parser;
 use wasm_bindgen::prelude::*;
 use rand::SeedableRng;
 
-use crate::blenders::{mosaic,delayer,xfade,outbreaker,m4ze,samplicat};
+use crate::blenders::{mosaic,delayer,xfade,outbreaker,m4ze,samplicat,clouds,smear,vocode};
 use crate::maxgain;
+use crate::dynamics::limit;
+use crate::loudness::loudnorm;
+use crate::key::{key, Key, MIN_CONFIDENCE};
 
 type In<'a> = &'a [&'a [u8]];
 type Out = Result<Vec<u8>, String>;
@@ -20,12 +23,19 @@ pub fn get_blenders<'a>() -> HashMap<&'a str, BlenderFn<'a>> {
         ("xfade", xfade as BlenderFn),
         ("outbreaker", outbreaker as BlenderFn),
         ("m4ze", m4ze as BlenderFn),
-        ("samplicat", samplicat as BlenderFn)
+        ("samplicat", samplicat as BlenderFn),
+        ("clouds", clouds as BlenderFn),
+        ("smear", smear as BlenderFn),
+        ("vocode", vocode as BlenderFn)
     ])
 
 }
 
 pub fn blend<'a>(wavs: In<'a>, rng: &mut StdRng, blender: &str, post_fx: Option<&str>) -> Out{
+    blend_with_master(wavs, rng, blender, post_fx, "maxgain")
+}
+
+pub fn blend_with_master<'a>(wavs: In<'a>, rng: &mut StdRng, blender: &str, post_fx: Option<&str>, master: &str) -> Out{
     
     let blenders = get_blenders();
 
@@ -46,10 +56,67 @@ pub fn blend<'a>(wavs: In<'a>, rng: &mut StdRng, blender: &str, post_fx: Option<
         out = crate::fx::apply_fx_with_rng(&out?, rng, fx.to_string());
     }
 
-    maxgain(&out?)
+    apply_master(&out?, master)
 
 }
 
+// Picks `count` of `total` files, loading them with `load` in random order
+// until enough of them suit one anchor key, so layers do not clash. The first
+// file with a clear key is the anchor; files with no clear key (drums, noise)
+// suit any anchor. Other anchors are only tried once every file is loaded.
+pub fn pick_key_compatible<F>(total: usize, count: usize, rng: &mut StdRng, mut load: F) -> Result<Vec<Vec<u8>>, String>
+where
+    F: FnMut(usize) -> Result<Vec<u8>, String>,
+{
+    let mut order: Vec<usize> = (0..total).collect();
+    order.shuffle(rng);
+
+    let clear_key = |wav: &[u8]| key(wav).ok().filter(|k| k.confidence >= MIN_CONFIDENCE);
+    let suits = |file_key: &Option<Key>, anchor: &Option<Key>| match (file_key, anchor) {
+        (Some(k), Some(a)) => k.is_compatible(a),
+        _ => true,
+    };
+
+    let mut loaded: Vec<(Vec<u8>, Option<Key>)> = Vec::new();
+    let mut anchor: Option<Key> = None;
+    for i in order {
+        let wav = load(i)?;
+        let file_key = clear_key(&wav);
+        anchor = anchor.or(file_key);
+        loaded.push((wav, file_key));
+
+        if loaded.iter().filter(|(_, k)| suits(k, &anchor)).count() >= count {
+            break;
+        }
+    }
+
+    let mut anchors = vec![anchor];
+    anchors.extend(loaded.iter().filter_map(|(_, k)| k.map(Some)).filter(|k| *k != anchor));
+    for candidate in anchors {
+        let picked: Vec<usize> = (0..loaded.len()).filter(|&i| suits(&loaded[i].1, &candidate)).take(count).collect();
+        if picked.len() == count {
+            if let Some(k) = candidate {
+                println!("Picking files compatible with {}", k.name());
+            }
+            return Ok(loaded.into_iter().enumerate().filter(|(i, _)| picked.contains(i)).map(|(_, (wav, _))| wav).collect());
+        }
+    }
+
+    Err(format!("Fewer than {} files share compatible keys.", count))
+}
+
+// Final loudness stage of a blend
+pub fn apply_master(wav: &[u8], master: &str) -> Out {
+    match master {
+        "maxgain" => maxgain(wav),
+        // Peak-normalize, then push 6 dB into a brickwall limiter
+        "limit" => limit(&maxgain(wav)?, 6.0, -0.3, 5.0, 80.0),
+        // Streaming loudness with a true-peak ceiling
+        "loudnorm" => loudnorm(wav, -14.0, -1.0),
+        _ => Err(format!("Invalid master provided: {}", master)),
+    }
+}
+
 #[derive(Parser)]
 pub struct BlendArgs{
 
@@ -64,8 +131,15 @@ pub struct BlendArgs{
     pub blender : String,
 
     #[arg()]
-    pub fx: Option<String>
+    pub fx: Option<String>,
+
+    /// Final stage applied to the blend (maxgain, limit, loudnorm)
+    #[arg(long, default_value = "maxgain")]
+    pub master: String,
 
+    /// Only pick files in compatible musical keys
+    #[arg(long)]
+    pub key_compatible: bool,
 
 }
 
@@ -78,10 +152,11 @@ pub fn get_blenders_js() -> Vec<String> {
 }
 
 #[wasm_bindgen]
-pub fn blend_js(wav1: Vec<u8>, wav2: Vec<u8>, wav3: Vec<u8>, seed: u64, blender: &str, post_fx: Option<String>) -> Result<Vec<u8>, String> {
+pub fn blend_js(wav1: Vec<u8>, wav2: Vec<u8>, wav3: Vec<u8>, seed: u64, blender: &str, post_fx: Option<String>, master: Option<String>) -> Result<Vec<u8>, String> {
     let mut rng = StdRng::seed_from_u64(seed);
     let wavs: Vec<&[u8]> = vec![wav1.as_slice(), wav2.as_slice(), wav3.as_slice()];
-    let blended_wav_bytes = self::blend(&wavs, &mut rng, blender, post_fx.as_deref())?;
+    let master = master.unwrap_or_else(|| "maxgain".to_string());
+    let blended_wav_bytes = self::blend_with_master(&wavs, &mut rng, blender, post_fx.as_deref(), &master)?;
 
     // Re-encode to 16-bit
     let mut reader = WavReader::new(Cursor::new(&blended_wav_bytes))
@@ -106,3 +181,44 @@ pub fn blend_js(wav1: Vec<u8>, wav2: Vec<u8>, wav3: Vec<u8>, seed: u64, blender:
 
     Ok(writer_buffer.into_inner())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::wrap_samples;
+    use hound::WavSpec;
+    use std::f32::consts::PI;
+
+    // Half a second per chord, each chord given as MIDI notes
+    fn chords(progression: &[[i32; 3]]) -> Vec<u8> {
+        let spec = WavSpec { channels: 1, sample_rate: 44100, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        let samples = progression
+            .iter()
+            .flat_map(|notes| {
+                (0..22050).map(move |i| {
+                    notes.iter().map(|&n| 0.2 * (2.0 * PI * 440.0 * 2.0f32.powf((n - 69) as f32 / 12.0) * i as f32 / 44100.0).sin()).sum::<f32>()
+                })
+            })
+            .collect();
+        wrap_samples(samples, spec).unwrap()
+    }
+
+    #[test]
+    fn test_pick_key_compatible() {
+        let c_major = chords(&[[60, 64, 67], [65, 69, 72], [67, 71, 74], [60, 64, 67]]);
+        let g_major = chords(&[[67, 71, 74], [60, 64, 67], [62, 66, 69], [67, 71, 74]]);
+        let f_sharp_major = chords(&[[66, 70, 73], [71, 75, 78], [73, 77, 80], [66, 70, 73]]);
+        let silence = crate::silence(1.0).unwrap();
+        let files = [c_major.clone(), f_sharp_major.clone(), g_major, c_major, f_sharp_major.clone(), silence];
+
+        for seed in 0..4 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let picked = pick_key_compatible(files.len(), 4, &mut rng, |i| Ok(files[i].clone())).unwrap();
+            assert_eq!(picked.len(), 4);
+            assert!(!picked.contains(&f_sharp_major), "F# major clashes with the other keys (seed {})", seed);
+        }
+
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!(pick_key_compatible(files.len(), 5, &mut rng, |i| Ok(files[i].clone())).is_err());
+    }
+}
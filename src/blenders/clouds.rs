@@ -0,0 +1,40 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use crate::{len, mix};
+use crate::granular::{granular, GranularSettings};
+use crate::window::Window;
+
+pub fn clouds(wavs: &[&[u8]], rng: &mut StdRng) -> Result<Vec<u8>, String> {
+
+    let duration_s = len(wavs[0])?.clamp(2.0, 8.0);
+
+    // A slow, smeared bed from the first two wavs...
+    let bed = GranularSettings {
+        grain_ms: rng.gen_range(80.0..=250.0),
+        density: rng.gen_range(15.0..=40.0),
+        position: rng.gen_range(0.0..=1.0),
+        jitter: rng.gen_range(0.2..=0.8),
+        pitch_jitter: *[0.0, 0.1, 12.0].choose(rng).unwrap(),
+        window: Window::Hann,
+        spread: rng.gen_range(0.5..=1.0),
+        duration_s,
+    };
+
+    // ...and a sparse spray of short grains from the third
+    let spray = GranularSettings {
+        grain_ms: rng.gen_range(10.0..=40.0),
+        density: rng.gen_range(5.0..=20.0),
+        position: rng.gen_range(0.0..=1.0),
+        jitter: rng.gen_range(0.0..=0.3),
+        pitch_jitter: rng.gen_range(0.0..=7.0),
+        window: Window::Gaussian,
+        spread: 1.0,
+        duration_s,
+    };
+
+    let a = granular(&wavs[..2], &bed, rng)?;
+    let b = granular(&wavs[2..3], &spray, rng)?;
+
+    mix(&a, &b, false)
+}
@@ -1,12 +1,16 @@
-use crate::{mix, silence, add, cut, x, normalize_speed};
+use crate::{mix, silence, add, cut, cut_bars, x, normalize_speed};
+use crate::imaging::balance;
 use rand::Rng;
 use rand::rngs::StdRng;
 
+// How far the original and the shadow lean away from each other
+const SPREAD: f32 = 0.5;
+
 
 pub fn delayer(wavs: &[&[u8]], rng: &mut StdRng) -> Result<Vec<u8>,String> {
 
-    let c0 = cut(wavs[0],"0","1/4")?;
-    let c1 = cut(wavs[1],"0","1/4")?;
+    let c0 = cut_bars(wavs[0], 1.0, "1/4")?;
+    let c1 = cut_bars(wavs[1], 1.0, "1/4")?;
     
     let (n0, n1, len) = normalize_speed(&c0, &c1)?;
 
@@ -14,7 +18,11 @@ pub fn delayer(wavs: &[&[u8]], rng: &mut StdRng) -> Result<Vec<u8>,String> {
     let pad = silence(offset)?;
     let end = &(len-offset).to_string();
     let shadow = add(&pad, &cut(&n1,"0",end)?)?;    
-    let out = mix(&n0,&shadow, rng.gen_bool(0.2))?;
+
+    // Original and shadow lean to opposite sides, each keeping its stereo image
+    let left = balance(&n0, -SPREAD)?;
+    let right = balance(&shadow, SPREAD)?;
+    let out = mix(&left, &right, rng.gen_bool(0.2))?;
 
     x(&out, 4)
         
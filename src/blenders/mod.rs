@@ -13,3 +13,12 @@ pub use m4ze::m4ze;
 
 pub mod samplicat;
 pub use samplicat::samplicat;
+
+pub mod clouds;
+pub use clouds::clouds;
+
+pub mod smear;
+pub use smear::smear;
+
+pub mod vocode;
+pub use vocode::vocode;
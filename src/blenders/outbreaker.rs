@@ -1,4 +1,4 @@
-use crate::{add, cut, resize, len, speed, chop, gain, fade, split};
+use crate::{add, cut, cut_bars, resize, len, speed, chop, gain, fade, split};
 use rand::Rng;
 use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
@@ -7,7 +7,7 @@ pub fn outbreaker(wavs: &[&[u8]], rng: &mut StdRng) -> Result<Vec<u8>, String> {
     let split_options = [4, 8, 16, 32];
 
     let s1 = wavs[0];
-    let s1_cut = cut(s1, "0", "1/4")?;
+    let s1_cut = cut_bars(s1, 1.0, "1/4")?;
     let s1_split_n = *split_options.choose(rng).unwrap();
     let arr0 = split(&s1_cut, s1_split_n as usize)?;
     if arr0.is_empty() {
@@ -16,7 +16,7 @@ pub fn outbreaker(wavs: &[&[u8]], rng: &mut StdRng) -> Result<Vec<u8>, String> {
     let a = arr0[0].clone();
 
     let s2 = wavs[1];
-    let s2_cut = cut(s2, "0", "1/4")?;
+    let s2_cut = cut_bars(s2, 1.0, "1/4")?;
     let s2_split_n = *split_options.choose(rng).unwrap();
     let arr1 = split(&s2_cut, s2_split_n as usize)?;
     if arr1.len() < 2 {
@@ -26,7 +26,7 @@ pub fn outbreaker(wavs: &[&[u8]], rng: &mut StdRng) -> Result<Vec<u8>, String> {
     let b = resize(&arr1[1], a_len)?;
 
     let s3 = wavs[2];
-    let s3_cut = cut(s3, "0", "1/4")?;
+    let s3_cut = cut_bars(s3, 1.0, "1/4")?;
     let s3_split_n = *split_options.choose(rng).unwrap();
     let arr2 = split(&s3_cut, s3_split_n as usize)?;
     if arr2.len() < 3 {
@@ -0,0 +1,26 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+use crate::{cut_bars, mix, x, normalize_speed};
+use crate::spectral::{spectral_blur, spectral_freeze, frequency_shift};
+use crate::stft::StftSettings;
+
+pub fn smear(wavs: &[&[u8]], rng: &mut StdRng) -> Result<Vec<u8>, String> {
+
+    let c0 = cut_bars(wavs[0], 1.0, "1/4")?;
+    let c1 = cut_bars(wavs[1], 1.0, "1/4")?;
+
+    let (n0, n1, _) = normalize_speed(&c0, &c1)?;
+    let settings = StftSettings::default();
+
+    // One layer washes out, the other hangs on a single moment
+    let washed = spectral_blur(&n0, rng.gen_range(0.85..0.98), &settings)?;
+    let mut held = spectral_freeze(&n1, ["1/8", "1/4", "1/2"][rng.gen_range(0..3)], &settings)?;
+
+    if rng.gen_bool(0.3) {
+        held = frequency_shift(&held, rng.gen_range(-300.0..=300.0), &settings)?;
+    }
+
+    let out = mix(&washed, &held, rng.gen_bool(0.5))?;
+
+    x(&out, 4)
+}
@@ -0,0 +1,21 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+use crate::{cut_bars, x, normalize_speed};
+use crate::vocoder::vocoder;
+
+pub fn vocode(wavs: &[&[u8]], rng: &mut StdRng) -> Result<Vec<u8>, String> {
+
+    let carrier = cut_bars(wavs[0], 1.0, "1/4")?;
+    let modulator = cut_bars(wavs[1], 1.0, "1/4")?;
+
+    let (c, m, _) = normalize_speed(&carrier, &modulator)?;
+
+    let bands = [8, 12, 16, 24, 32][rng.gen_range(0..5)];
+    let attack = rng.gen_range(1.0..=10.0);
+    let release = rng.gen_range(20.0..=120.0);
+    let formant = rng.gen_range(-5.0..=5.0);
+
+    let out = vocoder(&c, &m, bands, attack, release, formant)?;
+
+    x(&out, 4)
+}
@@ -1,11 +1,11 @@
-use crate::{normalize_speed,fade,mix,cut,x};
+use crate::{normalize_speed,fade,mix,cut_bars,x};
 use rand::rngs::StdRng;
 use rand::Rng;
 
 pub fn xfade(wavs:&[&[u8]], rng: &mut StdRng) -> Result<Vec<u8>, String>{
 
-    let f1 = cut(wavs[0],"0","1/4")?;
-    let f2 = cut(wavs[1],"0","1/4")?;
+    let f1 = cut_bars(wavs[0], 1.0, "1/4")?;
+    let f2 = cut_bars(wavs[1], 1.0, "1/4")?;
 
     let (w1,w2,_) = normalize_speed(&f1,&f2)?;
 
@@ -0,0 +1,216 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::get_samples;
+use crate::onset::onset_strength;
+use crate::{cut, len, resize};
+
+// Tempo estimates are pulled towards 120 BPM, with a spread of one octave,
+// which settles most half/double tempo ambiguities the usual way
+const PRIOR_CENTER_BPM: f32 = 120.0;
+const PRIOR_OCTAVES: f32 = 1.0;
+// Autocorrelation at one, two and four beat periods, and how much each counts
+const METRE_WEIGHTS: [(usize, f32); 3] = [(1, 1.0), (2, 0.5), (4, 0.25)];
+// Below this confidence the tempo is not trusted for cutting or stretching
+pub const MIN_CONFIDENCE: f32 = 0.3;
+// Tempo range when matching loops. Starting above 60 BPM keeps the analysis
+// short enough for a single bar of a typical loop.
+const MATCH_MIN_BPM: f32 = 80.0;
+const MATCH_MAX_BPM: f32 = 200.0;
+
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Tempo {
+    pub bpm: f32,
+    // Autocorrelation at the beat period relative to that at lag zero, 0..1
+    pub confidence: f32,
+    // Beat positions in seconds
+    #[wasm_bindgen(getter_with_clone)]
+    pub beats: Vec<f32>,
+}
+
+// Estimates the tempo from the autocorrelation of the onset strength, then
+// places the beat grid on the phase that lines up with the most onsets
+pub fn bpm(input_wav: &[u8], min_bpm: f32, max_bpm: f32) -> Result<Tempo, String> {
+    if min_bpm <= 0.0 || max_bpm <= min_bpm {
+        return Err("Tempo range must be positive and increasing.".to_string());
+    }
+
+    let (samples, spec) = get_samples(input_wav)?;
+    let (mut strength, frame_rate) = onset_strength(&samples, spec.channels as usize, spec.sample_rate)?;
+
+    let min_lag = ((60.0 * frame_rate / max_bpm).floor() as usize).max(1);
+    let max_lag = (60.0 * frame_rate / min_bpm).ceil() as usize;
+    if strength.len() < 2 * (max_lag + 2) {
+        return Err("Input is too short to detect the tempo.".to_string());
+    }
+
+    let mean = strength.iter().sum::<f32>() / strength.len() as f32;
+    strength.iter_mut().for_each(|s| *s -= mean);
+
+    let autocorrelation: Vec<f32> = (0..strength.len() / 2)
+        .map(|lag| {
+            let products = strength.iter().zip(&strength[lag..]).map(|(a, b)| a * b).sum::<f32>();
+            products / (strength.len() - lag) as f32
+        })
+        .collect();
+    if autocorrelation[0] <= 0.0 {
+        return Err("Input has no onsets to detect the tempo from.".to_string());
+    }
+
+    // Beats that also line up at two and four beats apart (duple meter) score higher
+    let score = |lag: usize| {
+        let octaves = (60.0 * frame_rate / lag as f32 / PRIOR_CENTER_BPM).log2() / PRIOR_OCTAVES;
+        let metre: f32 = METRE_WEIGHTS
+            .iter()
+            .map(|&(multiple, weight)| weight * autocorrelation.get(lag * multiple).copied().unwrap_or(0.0))
+            .sum();
+        metre * (-0.5 * octaves * octaves).exp()
+    };
+    let best = (min_lag..=max_lag).max_by(|&a, &b| score(a).total_cmp(&score(b))).unwrap();
+
+    // Parabolic interpolation between lags for a finer period
+    let (left, centre, right) = (autocorrelation[best - 1], autocorrelation[best], autocorrelation[best + 1]);
+    let curvature = left - 2.0 * centre + right;
+    let offset = if curvature < 0.0 { (0.5 * (left - right) / curvature).clamp(-0.5, 0.5) } else { 0.0 };
+    let period = best as f32 + offset;
+
+    let phase = (0..best)
+        .max_by(|&a, &b| {
+            let score = |phase: usize| {
+                (0..)
+                    .map(|k| (phase as f32 + k as f32 * period).round() as usize)
+                    .take_while(|&i| i < strength.len())
+                    .map(|i| strength[i])
+                    .sum::<f32>()
+            };
+            score(a).total_cmp(&score(b))
+        })
+        .unwrap();
+
+    let beats = (0..)
+        .map(|k| (phase as f32 + k as f32 * period) / frame_rate)
+        .take_while(|&t| t < samples.len() as f32 / spec.channels as f32 / spec.sample_rate as f32)
+        .collect();
+
+    Ok(Tempo {
+        bpm: 60.0 * frame_rate / period,
+        confidence: (centre / autocorrelation[0]).clamp(0.0, 1.0),
+        beats,
+    })
+}
+
+// Cuts `bars` bars of 4/4 starting on the first beat, or falls back to a
+// plain cut of `fallback` from the start when the tempo is unclear
+pub fn cut_bars(input_wav: &[u8], bars: f32, fallback: &str) -> Result<Vec<u8>, String> {
+    let duration = len(input_wav)?;
+    if let Ok(tempo) = bpm(input_wav, 60.0, 200.0) {
+        let bar_length = bars * 240.0 / tempo.bpm;
+        let start = tempo.beats.first().copied().unwrap_or(0.0);
+        if tempo.confidence >= MIN_CONFIDENCE && start + bar_length <= duration {
+            return cut(input_wav, &start.to_string(), &bar_length.to_string());
+        }
+    }
+    cut(input_wav, "0", fallback)
+}
+
+// Opt-in, tempo-aware alternative to `normalize_speed`: stretches two loops to
+// their common tempo (after folding out double/half tempo) and trims both to
+// the whole number of bars they share, so the pair still loops together.
+// Returns the tempo they now share, or an error when either tempo is unclear.
+pub fn normalize_tempo(wav1: &[u8], wav2: &[u8]) -> Result<(Vec<u8>, Vec<u8>, f32), String> {
+    let detected1 = bpm(wav1, MATCH_MIN_BPM, MATCH_MAX_BPM)?;
+    let detected2 = bpm(wav2, MATCH_MIN_BPM, MATCH_MAX_BPM)?;
+    if detected1.confidence < MIN_CONFIDENCE || detected2.confidence < MIN_CONFIDENCE {
+        return Err("Tempo is not clear enough to match.".to_string());
+    }
+    let tempo1 = detected1.bpm;
+    let mut tempo2 = detected2.bpm;
+    while tempo2 / tempo1 > 1.5 {
+        tempo2 /= 2.0;
+    }
+    while tempo2 / tempo1 < 0.75 {
+        tempo2 *= 2.0;
+    }
+
+    let target = (tempo1 + tempo2) / 2.0;
+    let bar = 240.0 / target;
+    let bars_in = |wav: &[u8], tempo: f32| -> Result<f32, String> { Ok((len(wav)? * tempo / target / bar).round().max(1.0)) };
+    let (bars1, bars2) = (bars_in(wav1, tempo1)?, bars_in(wav2, tempo2)?);
+    let bars = bars1.min(bars2);
+
+    // Keep the first `bars` bars of each loop, then stretch them to exactly that many bars
+    let o1 = resize(&cut(wav1, "0", &(len(wav1)? * bars / bars1).to_string())?, bars * bar)?;
+    let o2 = resize(&cut(wav2, "0", &(len(wav2)? * bars / bars2).to_string())?, bars * bar)?;
+    Ok((o1, o2, target))
+}
+
+#[wasm_bindgen]
+pub fn bpm_js(input_wav: &[u8], min_bpm: f32, max_bpm: f32) -> Result<Tempo, JsValue> {
+    bpm(input_wav, min_bpm, max_bpm).map_err(|e| JsValue::from_str(&e))
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Detects the tempo and beat positions of a WAV file", long_about = None)]
+pub struct BpmArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Lowest tempo considered
+    #[arg(long, default_value_t = 60.0)]
+    pub min: f32,
+
+    /// Highest tempo considered
+    #[arg(long, default_value_t = 200.0)]
+    pub max: f32,
+
+    /// Print the beat positions as well
+    #[arg(long)]
+    pub beats: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::wrap_samples;
+    use hound::{WavSpec, SampleFormat};
+
+    // Clicks every `60 / tempo` seconds starting at `offset`
+    fn click_track(tempo: f32, offset: f32, secs: f32) -> Vec<u8> {
+        let spec = WavSpec { channels: 1, sample_rate: 44100, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        let mut samples = vec![0.0f32; (44100.0 * secs) as usize];
+        let mut t = offset;
+        while t < secs {
+            let start = (t * 44100.0) as usize;
+            for (i, s) in samples.iter_mut().skip(start).take(400).enumerate() {
+                *s = (i as f32 * 0.4).sin() * (-(i as f32) / 100.0).exp();
+            }
+            t += 60.0 / tempo;
+        }
+        wrap_samples(samples, spec).unwrap()
+    }
+
+    #[test]
+    fn test_bpm_of_click_track() {
+        let tempo = bpm(&click_track(120.0, 0.1, 8.0), 60.0, 200.0).expect("bpm function failed");
+
+        assert!((tempo.bpm - 120.0).abs() < 1.0, "Detected {} BPM", tempo.bpm);
+        assert!(tempo.confidence > MIN_CONFIDENCE);
+        assert!((tempo.beats[0] - 0.1).abs() < 0.02, "First beat at {}", tempo.beats[0]);
+        assert!((tempo.beats[4] - tempo.beats[0] - 2.0).abs() < 0.03);
+    }
+
+    #[test]
+    fn test_cut_bars_and_normalize_tempo() {
+        let track = click_track(100.0, 0.0, 8.0);
+        let bar = cut_bars(&track, 1.0, "1/4").unwrap();
+        assert!((len(&bar).unwrap() - 2.4).abs() < 0.05, "One bar at 100 BPM lasts 2.4 s");
+
+        // Four bars at 120 BPM and two at 100 BPM share two bars at 110 BPM
+        let (matched, stretched, tempo) = normalize_tempo(&click_track(120.0, 0.0, 8.0), &click_track(100.0, 0.0, 4.8)).unwrap();
+        assert!((tempo - 110.0).abs() < 1.5);
+        assert_eq!(len(&matched).unwrap(), len(&stretched).unwrap());
+        assert!((len(&stretched).unwrap() - 2.0 * 240.0 / tempo).abs() < 0.01);
+        assert!(normalize_tempo(&track, &crate::silence(4.0).unwrap()).is_err());
+    }
+}
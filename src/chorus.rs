@@ -0,0 +1,144 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::{get_samples, wrap_samples, clamp_samples};
+use crate::delay::DelayLine;
+use crate::lfo::{Lfo, Waveform};
+
+pub fn chorus(
+    input_wav: &[u8],
+    voices: u32,         // Number of modulated copies layered over the input
+    delay_ms: f32,       // Base delay in milliseconds (typically 10-30)
+    depth_ms: f32,       // Depth of modulation in milliseconds
+    rate_hz: f32,        // LFO rate in Hz
+    spread: f32,         // Stereo spread (0.0 mono to 1.0 opposite phase between channels)
+    waveform: Waveform,  // LFO shape
+) -> Result<Vec<u8>, String> {
+    if voices == 0 {
+        return Err("Chorus needs at least one voice.".to_string());
+    }
+
+    let (samples, spec) = get_samples(input_wav)?;
+
+    let sample_rate = spec.sample_rate as f32;
+    let channels = spec.channels as usize;
+    let voices = voices as usize;
+
+    let max_delay_samples = (((delay_ms + depth_ms) / 1000.0 * sample_rate) as usize).max(1);
+
+    let mut delay_lines: Vec<DelayLine> = (0..channels).map(|_| DelayLine::new(max_delay_samples)).collect();
+
+    // One LFO per voice and channel: voices are spread evenly around the cycle
+    // and slightly detuned, channels are offset by the stereo spread
+    let mut lfos: Vec<Vec<Lfo>> = (0..channels)
+        .map(|ch| {
+            (0..voices)
+                .map(|v| {
+                    let phase = v as f32 / voices as f32 + ch as f32 * spread * 0.5;
+                    let detune = 1.0 + 0.1 * v as f32 / voices as f32;
+                    Lfo::with_phase(waveform, rate_hz * detune, sample_rate, phase)
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut output_samples = Vec::with_capacity(samples.len());
+
+    for frame in samples.chunks(channels) {
+        for (ch, &input_sample) in frame.iter().enumerate() {
+            delay_lines[ch].write(input_sample);
+
+            let mut wet = 0.0;
+            for lfo in lfos[ch].iter_mut() {
+                let modulated_delay_ms = delay_ms + depth_ms * lfo.next_unipolar();
+                wet += delay_lines[ch].read(modulated_delay_ms / 1000.0 * sample_rate);
+            }
+            wet /= voices as f32;
+
+            output_samples.push(0.5 * input_sample + 0.5 * wet);
+        }
+    }
+
+    clamp_samples(&mut output_samples);
+    wrap_samples(output_samples, spec)
+}
+
+#[wasm_bindgen]
+pub fn chorus_js(
+    input_wav: &[u8],
+    voices: u32,
+    delay_ms: f32,
+    depth_ms: f32,
+    rate_hz: f32,
+    spread: f32,
+    waveform: &str,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = waveform
+        .parse::<Waveform>()
+        .and_then(|waveform| chorus(input_wav, voices, delay_ms, depth_ms, rate_hz, spread, waveform));
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Applies a multi-voice chorus effect to a WAV file", long_about = None)]
+pub struct ChorusArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Number of voices
+    #[arg(long, default_value_t = 3)]
+    pub voices: u32,
+
+    /// Base delay in milliseconds
+    #[arg(long, default_value_t = 20.0)]
+    pub delay: f32,
+
+    /// Depth of modulation in milliseconds
+    #[arg(long, default_value_t = 5.0)]
+    pub depth: f32,
+
+    /// LFO rate in Hz
+    #[arg(long, default_value_t = 0.8)]
+    pub rate: f32,
+
+    /// Stereo spread (0.0 to 1.0)
+    #[arg(long, default_value_t = 0.5)]
+    pub spread: f32,
+
+    /// LFO waveform (sine, triangle, square, saw, random)
+    #[arg(long, default_value = "sine")]
+    pub waveform: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+
+    #[test]
+    fn test_chorus_effect() {
+        let input_wav = get_dummy();
+
+        let output_wav = chorus(&input_wav, 3, 20.0, 5.0, 0.8, 0.5, Waveform::Triangle)
+            .expect("chorus function failed");
+
+        let original_duration = len(&input_wav).unwrap();
+        let processed_duration = len(&output_wav).unwrap();
+        assert_eq!(original_duration, processed_duration, "Chorus should not change the duration");
+        assert_ne!(input_wav, output_wav, "Chorus should modify the audio content");
+    }
+
+    #[test]
+    fn test_chorus_requires_voices() {
+        let input_wav = get_dummy();
+        assert!(chorus(&input_wav, 0, 20.0, 5.0, 0.8, 0.5, Waveform::Sine).is_err(), "Zero voices should be rejected");
+    }
+}
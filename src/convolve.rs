@@ -0,0 +1,256 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::{get_samples, wrap_samples, clamp_samples};
+use crate::fft::{ifft, real_fft, next_pow2, Complex};
+use crate::time::resolve_time;
+
+// Smallest and largest FFT partition (in frames) used by the convolver
+const MIN_PARTITION: usize = 512;
+const MAX_PARTITION: usize = 16384;
+// The partition size grows with the IR so the number of partitions stays bounded
+const TARGET_PARTITIONS: usize = 16;
+// Length of the fade applied to the tail of a trimmed IR
+const TRIM_FADE_MS: f32 = 5.0;
+
+pub fn convolve(
+    input_wav: &[u8],
+    ir_wav: &[u8],
+    wet: f32,
+    dry: f32,
+    predelay_ms: f32,
+    ir_length: Option<&str>,
+    normalize_ir: bool,
+) -> Result<Vec<u8>, String> {
+    let (samples, spec) = get_samples(input_wav)?;
+    let (ir_samples, ir_spec) = get_samples(ir_wav)?;
+
+    if spec.sample_rate != ir_spec.sample_rate {
+        return Err("Sample rates do not match.".to_string());
+    }
+    if ir_spec.channels == 0 || ir_spec.channels > 2 {
+        return Err("Impulse response must be mono or stereo.".to_string());
+    }
+    if predelay_ms < 0.0 {
+        return Err("Pre-delay cannot be negative.".to_string());
+    }
+
+    let channels = spec.channels as usize;
+    let ir_channels = ir_spec.channels as usize;
+    let sample_rate = spec.sample_rate as f32;
+
+    let mut irs: Vec<Vec<f32>> = (0..ir_channels)
+        .map(|ch| ir_samples.iter().skip(ch).step_by(ir_channels).cloned().collect())
+        .collect();
+
+    if let Some(length) = ir_length {
+        let ir_duration = irs[0].len() as f32 / sample_rate;
+        let frames = (resolve_time(length, ir_duration)? * sample_rate) as usize;
+        let fade_frames = ((TRIM_FADE_MS / 1000.0 * sample_rate) as usize).min(frames);
+        for ir in irs.iter_mut() {
+            ir.truncate(frames);
+            let len = ir.len();
+            for i in 0..fade_frames.min(len) {
+                ir[len - 1 - i] *= i as f32 / fade_frames as f32;
+            }
+        }
+    }
+
+    if irs[0].is_empty() {
+        return Err("Impulse response is empty.".to_string());
+    }
+
+    if normalize_ir {
+        let energy = irs
+            .iter()
+            .map(|ir| ir.iter().map(|s| s * s).sum::<f32>())
+            .fold(0.0f32, f32::max);
+        if energy > 0.0 {
+            let scale = 1.0 / energy.sqrt();
+            for ir in irs.iter_mut() {
+                ir.iter_mut().for_each(|s| *s *= scale);
+            }
+        }
+    }
+
+    let predelay_frames = (predelay_ms / 1000.0 * sample_rate) as usize;
+    for ir in irs.iter_mut() {
+        ir.splice(0..0, std::iter::repeat_n(0.0, predelay_frames));
+    }
+
+    let input_frames = samples.len() / channels;
+    let ir_frames = irs[0].len();
+    let output_frames = input_frames + ir_frames - 1;
+
+    let mut output = vec![0.0; output_frames * channels];
+
+    for ch in 0..channels {
+        let dry_channel: Vec<f32> = samples.iter().skip(ch).step_by(channels).cloned().collect();
+        let wet_channel = convolve_partitioned(&dry_channel, &irs[ch % ir_channels], output_frames);
+
+        for i in 0..output_frames {
+            let dry_sample = dry_channel.get(i).cloned().unwrap_or(0.0);
+            output[i * channels + ch] = dry * dry_sample + wet * wet_channel[i];
+        }
+    }
+
+    clamp_samples(&mut output);
+    wrap_samples(output, spec)
+}
+
+// Uniformly partitioned overlap-save convolution. Each block of the input is
+// transformed once and multiplied against every IR partition through a
+// frequency-domain delay line, so the cost is linear in the input length.
+pub fn convolve_partitioned(signal: &[f32], ir: &[f32], output_len: usize) -> Vec<f32> {
+    let block = next_pow2(ir.len().div_ceil(TARGET_PARTITIONS)).clamp(MIN_PARTITION, MAX_PARTITION);
+    let fft_size = block * 2;
+
+    let partitions: Vec<Vec<Complex>> = ir
+        .chunks(block)
+        .map(|chunk| real_fft(chunk, fft_size))
+        .collect();
+    let num_partitions = partitions.len();
+
+    let mut delay_line = vec![vec![Complex::default(); fft_size]; num_partitions];
+    let mut head = 0;
+
+    let num_blocks = output_len.div_ceil(block);
+    let mut output = Vec::with_capacity(num_blocks * block);
+    let mut window = vec![0.0f32; fft_size];
+
+    for n in 0..num_blocks {
+        // Slide the input window: [previous block, current block]
+        window.copy_within(block.., 0);
+        for i in 0..block {
+            window[block + i] = signal.get(n * block + i).cloned().unwrap_or(0.0);
+        }
+
+        head = (head + num_partitions - 1) % num_partitions;
+        delay_line[head] = real_fft(&window, fft_size);
+
+        let mut accumulator = vec![Complex::default(); fft_size];
+        for (k, partition) in partitions.iter().enumerate() {
+            let spectrum = &delay_line[(head + k) % num_partitions];
+            for (acc, (x, h)) in accumulator.iter_mut().zip(spectrum.iter().zip(partition.iter())) {
+                *acc = *acc + *x * *h;
+            }
+        }
+
+        ifft(&mut accumulator);
+        output.extend(accumulator[block..].iter().map(|c| c.re));
+    }
+
+    output.truncate(output_len);
+    output
+}
+
+// Single-block FFT convolution of two short signals (full linear convolution)
+pub fn fft_convolve(a: &[f32], b: &[f32]) -> Vec<f32> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let out_len = a.len() + b.len() - 1;
+    let size = next_pow2(out_len);
+    let mut spectrum_a = real_fft(a, size);
+    let spectrum_b = real_fft(b, size);
+    for (x, h) in spectrum_a.iter_mut().zip(spectrum_b.iter()) {
+        *x = *x * *h;
+    }
+    ifft(&mut spectrum_a);
+    spectrum_a.iter().take(out_len).map(|c| c.re).collect()
+}
+
+#[wasm_bindgen]
+pub fn convolve_js(
+    input_wav: &[u8],
+    ir_wav: &[u8],
+    wet: f32,
+    dry: f32,
+    predelay_ms: f32,
+    ir_length: Option<String>,
+    normalize_ir: bool,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    match convolve(input_wav, ir_wav, wet, dry, predelay_ms, ir_length.as_deref(), normalize_ir) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Convolves a WAV file with an impulse response WAV", long_about = None)]
+pub struct ConvolveArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Impulse response WAV file (mono or stereo)
+    #[arg()]
+    pub ir: String,
+
+    /// Wet (convolved) level, 0.0 to 1.0
+    #[arg(long, default_value_t = 0.5)]
+    pub wet: f32,
+
+    /// Dry (original) level, 0.0 to 1.0
+    #[arg(long, default_value_t = 0.5)]
+    pub dry: f32,
+
+    /// Pre-delay in milliseconds before the IR starts
+    #[arg(long, default_value_t = 0.0)]
+    pub predelay: f32,
+
+    /// Trim the IR to this length (can be absolute or fraction like "1/2")
+    #[arg(long)]
+    pub trim: Option<String>,
+
+    /// Normalize the IR to unit energy
+    #[arg(short, long)]
+    pub normalize: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::get_dummy;
+    use crate::len::len;
+    use crate::cut::cut;
+
+    #[test]
+    fn test_convolve_adds_ir_tail() {
+        let input_wav = get_dummy();
+        let ir_wav = cut(&get_dummy(), "0", "0.25").unwrap();
+
+        let output_wav = convolve(&input_wav, &ir_wav, 0.5, 0.5, 10.0, None, true)
+            .expect("convolve function failed");
+
+        let input_duration = len(&input_wav).unwrap();
+        let ir_duration = len(&ir_wav).unwrap();
+        let output_duration = len(&output_wav).unwrap();
+
+        assert!((output_duration - (input_duration + ir_duration + 0.01)).abs() < 0.01, "Output should include the IR tail and pre-delay");
+        assert_ne!(input_wav, output_wav, "Convolution should modify the audio content");
+    }
+
+    #[test]
+    fn test_partitioned_matches_direct() {
+        let signal: Vec<f32> = (0..5000).map(|i| ((i * 7919) % 101) as f32 / 101.0 - 0.5).collect();
+        let ir: Vec<f32> = (0..3000).map(|i| (-(i as f32) / 400.0).exp() * ((i % 13) as f32 / 13.0 - 0.5)).collect();
+
+        let expected = fft_convolve(&signal, &ir);
+        let actual = convolve_partitioned(&signal, &ir, expected.len());
+
+        for (e, a) in expected.iter().zip(actual.iter()) {
+            assert!((e - a).abs() < 1e-3, "Partitioned convolution should match direct convolution");
+        }
+    }
+
+    #[test]
+    fn test_convolve_rejects_trimmed_to_nothing() {
+        let input_wav = get_dummy();
+        let result = convolve(&input_wav, &input_wav, 0.5, 0.5, 0.0, Some("0"), false);
+        assert!(result.is_err(), "Should fail with an empty impulse response");
+    }
+}
@@ -0,0 +1,221 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::{get_samples, wrap_samples, clamp_samples};
+use crate::filter::BandLimiter;
+use crate::time::resolve_ms_or_note;
+
+pub fn delay(
+    input_wav: &[u8],
+    time_ms: f32,         // Delay time in milliseconds
+    feedback: f32,        // Amount of the echo fed back into the line (-1.0 to 1.0, exclusive)
+    ping_pong: bool,      // Bounce echoes between left and right
+    lowcut_hz: Option<f32>,  // Highpass inside the feedback loop
+    highcut_hz: Option<f32>, // Lowpass inside the feedback loop
+    mix: f32,             // Wet/dry balance (0.0 dry, 1.0 wet)
+) -> Result<Vec<u8>, String> {
+    if time_ms <= 0.0 {
+        return Err("Delay time must be positive.".to_string());
+    }
+    if !(0.0..1.0).contains(&feedback.abs()) {
+        return Err("Feedback must be between -1.0 and 1.0 (exclusive).".to_string());
+    }
+
+    let (samples, spec) = get_samples(input_wav)?;
+    let sample_rate = spec.sample_rate as f32;
+    let channels = spec.channels as usize;
+    let num_frames = samples.len() / channels;
+
+    let delay_samples = ((time_ms / 1000.0 * sample_rate) as usize).max(1);
+
+    let mut lines = vec![vec![0.0f32; delay_samples]; channels];
+    let mut filters: Vec<BandLimiter> = (0..channels)
+        .map(|_| BandLimiter::new(lowcut_hz, highcut_hz, sample_rate))
+        .collect();
+    let mut pointer = 0;
+
+    let ping_pong = ping_pong && channels == 2;
+    let mut output = Vec::with_capacity(samples.len());
+    let mut echoes = vec![0.0f32; channels];
+
+    for i in 0..num_frames {
+        let frame = &samples[i * channels..(i + 1) * channels];
+
+        for (echo, line) in echoes.iter_mut().zip(lines.iter()) {
+            *echo = line[pointer];
+        }
+
+        if ping_pong {
+            // The input enters the left line only, and each side feeds the other
+            let mono_in = (frame[0] + frame[1]) / 2.0;
+            lines[0][pointer] = mono_in + feedback * filters[0].process(echoes[1]);
+            lines[1][pointer] = feedback * filters[1].process(echoes[0]);
+        } else {
+            for ch in 0..channels {
+                let fed_back = filters[ch].process(echoes[ch]);
+                lines[ch][pointer] = frame[ch] + feedback * fed_back;
+            }
+        }
+
+        for (dry, echo) in frame.iter().zip(echoes.iter()) {
+            output.push(dry * (1.0 - mix) + echo * mix);
+        }
+
+        pointer = (pointer + 1) % delay_samples;
+    }
+
+    clamp_samples(&mut output);
+    wrap_samples(output, spec)
+}
+
+// Circular buffer with fractional (linearly interpolated) reads, shared by the
+// modulated effects (flanger, chorus...)
+pub struct DelayLine {
+    buffer: Vec<f32>,
+    write_pointer: usize,
+}
+
+impl DelayLine {
+    pub fn new(max_delay_samples: usize) -> Self {
+        DelayLine {
+            buffer: vec![0.0; max_delay_samples.max(1) + 2],
+            write_pointer: 0,
+        }
+    }
+
+    // Reads the signal written `delay_samples` samples ago (at least one)
+    pub fn read(&self, delay_samples: f32) -> f32 {
+        let len = self.buffer.len();
+        let delay_samples = delay_samples.clamp(1.0, (len - 1) as f32);
+        let read_pointer = (self.write_pointer as f32 - delay_samples).rem_euclid(len as f32);
+
+        let index1 = read_pointer.floor() as usize % len;
+        let index2 = (index1 + 1) % len;
+        let frac = read_pointer.fract();
+
+        self.buffer[index1] * (1.0 - frac) + self.buffer[index2] * frac
+    }
+
+    pub fn write(&mut self, sample: f32) {
+        self.buffer[self.write_pointer] = sample;
+        self.write_pointer = (self.write_pointer + 1) % self.buffer.len();
+    }
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn delay_js(
+    input_wav: &[u8],
+    time: &str,
+    bpm: Option<f32>,
+    feedback: f32,
+    ping_pong: bool,
+    lowcut_hz: Option<f32>,
+    highcut_hz: Option<f32>,
+    mix: f32,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = resolve_ms_or_note(time, bpm)
+        .and_then(|time_ms| delay(input_wav, time_ms, feedback, ping_pong, lowcut_hz, highcut_hz, mix));
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Applies a (tempo-synced) delay to a WAV file", long_about = None)]
+pub struct DelayArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Delay time in milliseconds, or a note division like "1/8", "1/8d", "1/16t" (requires --bpm)
+    #[arg()]
+    pub time: String,
+
+    /// Tempo used to resolve note divisions
+    #[arg(long)]
+    pub bpm: Option<f32>,
+
+    /// Feedback amount (-1.0 to 1.0)
+    #[arg(long, default_value_t = 0.4, allow_hyphen_values = true)]
+    pub feedback: f32,
+
+    /// Bounce echoes between the left and right channels
+    #[arg(long)]
+    pub ping_pong: bool,
+
+    /// Low cut (highpass) frequency in the feedback loop, in Hz
+    #[arg(long)]
+    pub lowcut: Option<f32>,
+
+    /// High cut (lowpass) frequency in the feedback loop, in Hz
+    #[arg(long)]
+    pub highcut: Option<f32>,
+
+    /// Wet/dry mix (0.0 dry to 1.0 wet)
+    #[arg(long, default_value_t = 0.35)]
+    pub mix: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+    use hound::{WavSpec, SampleFormat};
+
+    #[test]
+    fn test_delay_effect() {
+        let input_wav = get_dummy();
+
+        let output_wav = delay(&input_wav, 250.0, 0.5, true, Some(200.0), Some(6000.0), 0.4)
+            .expect("delay function failed");
+
+        let original_duration = len(&input_wav).unwrap();
+        let processed_duration = len(&output_wav).unwrap();
+        assert_eq!(original_duration, processed_duration, "Delay should not change the duration");
+        assert_ne!(input_wav, output_wav, "Delay should modify the audio content");
+    }
+
+    #[test]
+    fn test_delay_ping_pong_alternates() {
+        let spec = WavSpec {
+            channels: 2,
+            sample_rate: 1000,
+            bits_per_sample: 32,
+            sample_format: SampleFormat::Float,
+        };
+        // A single click on both channels at the start, then silence
+        let mut samples = vec![0.0f32; 2 * 100];
+        samples[0] = 1.0;
+        samples[1] = 1.0;
+        let input_wav = wrap_samples(samples, spec).unwrap();
+
+        let output_wav = delay(&input_wav, 10.0, 0.5, true, None, None, 1.0).unwrap();
+        let (out, _) = get_samples(&output_wav).unwrap();
+
+        // First echo on the left after 10 frames, second on the right after 20
+        assert!(out[10 * 2].abs() > 0.1 && out[10 * 2 + 1].abs() < 1e-6, "First echo should be on the left");
+        assert!(out[20 * 2].abs() < 1e-6 && out[20 * 2 + 1].abs() > 0.1, "Second echo should be on the right");
+    }
+
+    #[test]
+    fn test_delay_line_fractional_read() {
+        let mut line = DelayLine::new(8);
+        for i in 0..4 {
+            line.write(i as f32);
+        }
+        assert_eq!(line.read(1.0), 3.0, "A delay of one should read the last sample");
+        assert!((line.read(1.5) - 2.5).abs() < 1e-6, "Fractional delays should interpolate");
+    }
+
+    #[test]
+    fn test_delay_rejects_runaway_feedback() {
+        let input_wav = get_dummy();
+        assert!(delay(&input_wav, 100.0, 1.0, false, None, None, 0.5).is_err(), "Feedback of 1.0 should be rejected");
+    }
+}
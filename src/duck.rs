@@ -0,0 +1,196 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::{get_samples, wrap_samples};
+use crate::dynamics::{amplitude_to_db, db_to_amplitude, gain_computer, EnvelopeFollower};
+use crate::filter::BandLimiter;
+
+#[derive(Clone, Debug)]
+pub struct DuckSettings {
+    pub threshold_db: f32,
+    pub ratio: f32,
+    // Maximum attenuation in dB, however loud the sidechain gets
+    pub depth_db: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+    // Optional band-limiting of the sidechain before detection
+    pub sidechain_lowcut: Option<f32>,
+    pub sidechain_highcut: Option<f32>,
+}
+
+impl Default for DuckSettings {
+    fn default() -> Self {
+        DuckSettings {
+            threshold_db: -24.0,
+            ratio: 8.0,
+            depth_db: 24.0,
+            attack_ms: 5.0,
+            release_ms: 150.0,
+            sidechain_lowcut: None,
+            sidechain_highcut: None,
+        }
+    }
+}
+
+// Attenuates `main_wav` following the envelope of `sidechain_wav`, which loops
+// when it is shorter
+pub fn duck(main_wav: &[u8], sidechain_wav: &[u8], settings: &DuckSettings) -> Result<Vec<u8>, String> {
+    if settings.ratio < 1.0 {
+        return Err("Ratio must be at least 1.0.".to_string());
+    }
+
+    let (mut samples, spec) = get_samples(main_wav)?;
+    let (sidechain, sidechain_spec) = get_samples(sidechain_wav)?;
+
+    if spec.sample_rate != sidechain_spec.sample_rate {
+        return Err("Sample rates do not match.".to_string());
+    }
+
+    let sample_rate = spec.sample_rate as f32;
+    let channels = spec.channels as usize;
+    let sidechain_channels = sidechain_spec.channels as usize;
+
+    let mut filter = BandLimiter::new(settings.sidechain_lowcut, settings.sidechain_highcut, sample_rate);
+    let mut follower = EnvelopeFollower::new(settings.attack_ms, settings.release_ms, sample_rate);
+
+    // A sidechain shorter than the main input loops, so a one-bar kick keeps pumping
+    let mut sidechain_frames = sidechain.chunks(sidechain_channels).cycle();
+
+    for frame in samples.chunks_mut(channels) {
+        // The sidechain is summed to mono (an empty one never ducks)
+        let key = sidechain_frames
+            .next()
+            .map(|sc| sc.iter().sum::<f32>() / sidechain_channels as f32)
+            .unwrap_or(0.0);
+        let envelope = follower.process(filter.process(key).abs());
+
+        let reduction = gain_computer(amplitude_to_db(envelope), settings.threshold_db, settings.ratio, 0.0)
+            .max(-settings.depth_db.abs());
+        let gain = db_to_amplitude(reduction);
+
+        for sample in frame.iter_mut() {
+            *sample *= gain;
+        }
+    }
+
+    wrap_samples(samples, spec)
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn duck_js(
+    main_wav: &[u8],
+    sidechain_wav: &[u8],
+    threshold_db: f32,
+    ratio: f32,
+    depth_db: f32,
+    attack_ms: f32,
+    release_ms: f32,
+    sidechain_lowcut: Option<f32>,
+    sidechain_highcut: Option<f32>,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let settings = DuckSettings { threshold_db, ratio, depth_db, attack_ms, release_ms, sidechain_lowcut, sidechain_highcut };
+    match duck(main_wav, sidechain_wav, &settings) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Ducks a WAV file by the envelope of a sidechain WAV", long_about = None)]
+pub struct DuckArgs {
+    /// Main WAV file (the one being ducked)
+    #[arg()]
+    pub input: String,
+
+    /// Sidechain WAV file (e.g. a kick loop)
+    #[arg()]
+    pub sidechain: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Threshold in dBFS
+    #[arg(long, default_value_t = -24.0, allow_hyphen_values = true)]
+    pub threshold: f32,
+
+    /// Ratio (e.g., 8.0 for 8:1)
+    #[arg(long, default_value_t = 8.0)]
+    pub ratio: f32,
+
+    /// Maximum attenuation in dB
+    #[arg(long, default_value_t = 24.0)]
+    pub depth: f32,
+
+    /// Attack time in milliseconds
+    #[arg(long, default_value_t = 5.0)]
+    pub attack: f32,
+
+    /// Release time in milliseconds
+    #[arg(long, default_value_t = 150.0)]
+    pub release: f32,
+
+    /// Sidechain low cut (highpass) frequency in Hz
+    #[arg(long)]
+    pub sc_lowcut: Option<f32>,
+
+    /// Sidechain high cut (lowpass) frequency in Hz, e.g. 150 to key off a kick
+    #[arg(long)]
+    pub sc_highcut: Option<f32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+    use crate::silence::silence;
+    use crate::utils::wrap_samples;
+    use hound::{WavSpec, SampleFormat};
+
+    #[test]
+    fn test_duck_attenuates_main() {
+        let main_wav = get_dummy();
+        let sidechain_wav = get_dummy();
+
+        let output_wav = duck(&main_wav, &sidechain_wav, &DuckSettings::default()).expect("duck function failed");
+
+        let (input_samples, _) = get_samples(&main_wav).unwrap();
+        let (output_samples, _) = get_samples(&output_wav).unwrap();
+        let input_energy: f32 = input_samples.iter().map(|s| s * s).sum();
+        let output_energy: f32 = output_samples.iter().map(|s| s * s).sum();
+
+        assert_eq!(len(&main_wav).unwrap(), len(&output_wav).unwrap(), "Ducking should keep the main duration");
+        assert!(output_energy < input_energy, "Ducking should reduce the energy of the main signal");
+    }
+
+    #[test]
+    fn test_duck_silent_sidechain_is_transparent() {
+        let main_wav = get_dummy();
+        let sidechain_wav = silence(1.0).unwrap();
+
+        let output_wav = duck(&main_wav, &sidechain_wav, &DuckSettings::default()).unwrap();
+
+        let (input_samples, _) = get_samples(&main_wav).unwrap();
+        let (output_samples, _) = get_samples(&output_wav).unwrap();
+        for (a, b) in input_samples.iter().zip(output_samples.iter()) {
+            assert!((a - b).abs() < 1e-6, "A silent sidechain should leave the main signal untouched");
+        }
+    }
+
+    #[test]
+    fn test_duck_short_sidechain_loops() {
+        let spec = WavSpec { channels: 1, sample_rate: 44100, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        let main_wav = wrap_samples(vec![0.5; 88200], spec).unwrap();
+        // Half a second with a 50 ms hit at the start, like a one-beat kick loop
+        let sidechain_wav = wrap_samples((0..22050).map(|i| if i < 2205 { 0.8 } else { 0.0 }).collect(), spec).unwrap();
+
+        let output_wav = duck(&main_wav, &sidechain_wav, &DuckSettings::default()).unwrap();
+        let (output, _) = get_samples(&output_wav).unwrap();
+
+        // Ducked on the fourth repeat of the hit, recovered just before it
+        let at = |secs: f32| output[(secs * 44100.0) as usize];
+        assert!(at(1.53) < 0.1, "Still ducked after the sidechain ran out once: {}", at(1.53));
+        assert!(at(1.49) > 0.4, "Should recover between hits: {}", at(1.49));
+    }
+}
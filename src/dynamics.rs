@@ -0,0 +1,417 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use std::collections::VecDeque;
+use std::str::FromStr;
+use crate::utils::{get_samples, wrap_samples};
+
+pub fn db_to_amplitude(db: f32) -> f32 {
+    10.0_f32.powf(db / 20.0)
+}
+
+pub fn amplitude_to_db(amplitude: f32) -> f32 {
+    20.0 * amplitude.max(1e-9).log10()
+}
+
+// One-pole smoothing coefficient for a time constant in milliseconds
+pub fn time_coefficient(time_ms: f32, sample_rate: f32) -> f32 {
+    if time_ms <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / (time_ms / 1000.0 * sample_rate)).exp()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Detector {
+    Peak,
+    Rms,
+}
+
+impl FromStr for Detector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "peak" => Ok(Detector::Peak),
+            "rms" => Ok(Detector::Rms),
+            _ => Err(format!("Detector not recognized: {}", s)),
+        }
+    }
+}
+
+// Attack/release envelope follower. Rises with the attack time constant and
+// falls with the release one.
+pub struct EnvelopeFollower {
+    attack: f32,
+    release: f32,
+    envelope: f32,
+}
+
+impl EnvelopeFollower {
+    pub fn new(attack_ms: f32, release_ms: f32, sample_rate: f32) -> Self {
+        EnvelopeFollower {
+            attack: time_coefficient(attack_ms, sample_rate),
+            release: time_coefficient(release_ms, sample_rate),
+            envelope: 0.0,
+        }
+    }
+
+    pub fn process(&mut self, level: f32) -> f32 {
+        let coefficient = if level > self.envelope { self.attack } else { self.release };
+        self.envelope = coefficient * self.envelope + (1.0 - coefficient) * level;
+        self.envelope
+    }
+}
+
+// Level detector feeding the gain computer: instantaneous peak or a short
+// running RMS
+pub struct LevelDetector {
+    detector: Detector,
+    rms_coefficient: f32,
+    mean_square: f32,
+}
+
+// Averaging window of the RMS detector
+const RMS_WINDOW_MS: f32 = 10.0;
+
+impl LevelDetector {
+    pub fn new(detector: Detector, sample_rate: f32) -> Self {
+        LevelDetector {
+            detector,
+            rms_coefficient: time_coefficient(RMS_WINDOW_MS, sample_rate),
+            mean_square: 0.0,
+        }
+    }
+
+    pub fn process(&mut self, sample: f32) -> f32 {
+        match self.detector {
+            Detector::Peak => sample.abs(),
+            Detector::Rms => {
+                self.mean_square = self.rms_coefficient * self.mean_square
+                    + (1.0 - self.rms_coefficient) * sample * sample;
+                self.mean_square.sqrt()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CompressorSettings {
+    pub threshold_db: f32,
+    pub ratio: f32,
+    pub knee_db: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+    pub makeup_db: f32,
+    pub detector: Detector,
+    pub stereo_link: bool,
+}
+
+impl Default for CompressorSettings {
+    fn default() -> Self {
+        CompressorSettings {
+            threshold_db: -18.0,
+            ratio: 4.0,
+            knee_db: 6.0,
+            attack_ms: 10.0,
+            release_ms: 120.0,
+            makeup_db: 0.0,
+            detector: Detector::Rms,
+            stereo_link: true,
+        }
+    }
+}
+
+// Static curve of the compressor: gain change in dB (<= 0) for an input level
+pub fn gain_computer(level_db: f32, threshold_db: f32, ratio: f32, knee_db: f32) -> f32 {
+    let overshoot = level_db - threshold_db;
+    let output_db = if 2.0 * overshoot < -knee_db {
+        level_db
+    } else if knee_db > 0.0 && 2.0 * overshoot.abs() <= knee_db {
+        level_db + (1.0 / ratio - 1.0) * (overshoot + knee_db / 2.0).powi(2) / (2.0 * knee_db)
+    } else {
+        threshold_db + overshoot / ratio
+    };
+    output_db - level_db
+}
+
+pub fn compress(input_wav: &[u8], settings: &CompressorSettings) -> Result<Vec<u8>, String> {
+    if settings.ratio < 1.0 {
+        return Err("Ratio must be at least 1.0.".to_string());
+    }
+
+    let (mut samples, spec) = get_samples(input_wav)?;
+    let sample_rate = spec.sample_rate as f32;
+    let channels = spec.channels as usize;
+
+    // Linked stereo uses a single detector/gain for all channels
+    let paths = if settings.stereo_link { 1 } else { channels };
+    let mut detectors: Vec<LevelDetector> = (0..paths).map(|_| LevelDetector::new(settings.detector, sample_rate)).collect();
+    let attack = time_coefficient(settings.attack_ms, sample_rate);
+    let release = time_coefficient(settings.release_ms, sample_rate);
+    let mut reductions = vec![0.0f32; paths];
+
+    for frame in samples.chunks_mut(channels) {
+        if settings.stereo_link {
+            let loudest = frame.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+            let level = detectors[0].process(loudest);
+            reductions[0] = smooth_reduction(reductions[0], level, settings, attack, release);
+        } else {
+            for (ch, &sample) in frame.iter().enumerate() {
+                let level = detectors[ch].process(sample);
+                reductions[ch] = smooth_reduction(reductions[ch], level, settings, attack, release);
+            }
+        }
+
+        for (ch, sample) in frame.iter_mut().enumerate() {
+            let reduction = reductions[if settings.stereo_link { 0 } else { ch }];
+            *sample *= db_to_amplitude(reduction + settings.makeup_db);
+        }
+    }
+
+    wrap_samples(samples, spec)
+}
+
+// Moves the current gain reduction towards the static curve's target: more
+// reduction follows the attack, recovering follows the release
+fn smooth_reduction(current: f32, level: f32, settings: &CompressorSettings, attack: f32, release: f32) -> f32 {
+    let target = gain_computer(amplitude_to_db(level), settings.threshold_db, settings.ratio, settings.knee_db);
+    let coefficient = if target < current { attack } else { release };
+    coefficient * current + (1.0 - coefficient) * target
+}
+
+// Running minimum over the next `window` values (inclusive of the current one)
+fn forward_minimum(values: &[f32], window: usize) -> Vec<f32> {
+    let mut result = vec![0.0; values.len()];
+    let mut candidates: VecDeque<usize> = VecDeque::new();
+
+    for i in (0..values.len()).rev() {
+        while candidates.back().is_some_and(|&j| values[j] >= values[i]) {
+            candidates.pop_back();
+        }
+        candidates.push_back(i);
+        while candidates.front().is_some_and(|&j| j > i + window) {
+            candidates.pop_front();
+        }
+        result[i] = values[*candidates.front().unwrap()];
+    }
+
+    result
+}
+
+pub fn limit(
+    input_wav: &[u8],
+    drive_db: f32,     // Gain applied before limiting
+    ceiling_db: f32,   // Maximum output peak in dBFS
+    lookahead_ms: f32, // How early the limiter starts reducing gain before a peak
+    release_ms: f32,   // Recovery time after a peak
+) -> Result<Vec<u8>, String> {
+    if ceiling_db > 0.0 {
+        return Err("Ceiling cannot be above 0 dBFS.".to_string());
+    }
+
+    let (mut samples, spec) = get_samples(input_wav)?;
+    let sample_rate = spec.sample_rate as f32;
+    let channels = spec.channels as usize;
+
+    let drive = db_to_amplitude(drive_db);
+    let ceiling = db_to_amplitude(ceiling_db);
+    let lookahead = ((lookahead_ms / 1000.0 * sample_rate) as usize).max(1);
+    let release = time_coefficient(release_ms, sample_rate);
+
+    samples.iter_mut().for_each(|s| *s *= drive);
+
+    // Gain each frame needs to stay under the ceiling (linked across channels)
+    let required: Vec<f32> = samples
+        .chunks(channels)
+        .map(|frame| {
+            let peak = frame.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+            if peak > ceiling { ceiling / peak } else { 1.0 }
+        })
+        .collect();
+
+    // The forward minimum looks ahead for peaks, and averaging it over the
+    // lookahead window ramps the gain down smoothly while never exceeding
+    // what any upcoming peak requires
+    let minimum = forward_minimum(&required, lookahead);
+    let mut ramp = Vec::with_capacity(minimum.len());
+    let mut running_sum = 0.0f64;
+    for i in 0..minimum.len() {
+        running_sum += minimum[i] as f64;
+        if i >= lookahead {
+            running_sum -= minimum[i - lookahead] as f64;
+        }
+        // Frames before the start count as the first frame, whose window
+        // already covers everything up to the lookahead
+        let padding = lookahead.saturating_sub(i + 1) as f64 * minimum[0] as f64;
+        ramp.push(((running_sum + padding) / lookahead as f64) as f32);
+    }
+
+    let mut gain = 1.0f32;
+    for (frame, &target) in samples.chunks_mut(channels).zip(ramp.iter()) {
+        gain = if target < gain { target } else { release * gain + (1.0 - release) * target };
+        for sample in frame.iter_mut() {
+            *sample = (*sample * gain).clamp(-ceiling, ceiling);
+        }
+    }
+
+    wrap_samples(samples, spec)
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn compress_js(
+    input_wav: &[u8],
+    threshold_db: f32,
+    ratio: f32,
+    knee_db: f32,
+    attack_ms: f32,
+    release_ms: f32,
+    makeup_db: f32,
+    detector: &str,
+    stereo_link: bool,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = detector.parse::<Detector>().and_then(|detector| {
+        let settings = CompressorSettings { threshold_db, ratio, knee_db, attack_ms, release_ms, makeup_db, detector, stereo_link };
+        compress(input_wav, &settings)
+    });
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[wasm_bindgen]
+pub fn limit_js(
+    input_wav: &[u8],
+    drive_db: f32,
+    ceiling_db: f32,
+    lookahead_ms: f32,
+    release_ms: f32,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    match limit(input_wav, drive_db, ceiling_db, lookahead_ms, release_ms) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Compresses the dynamic range of a WAV file", long_about = None)]
+pub struct CompressArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Threshold in dBFS
+    #[arg(long, default_value_t = -18.0, allow_hyphen_values = true)]
+    pub threshold: f32,
+
+    /// Compression ratio (e.g., 4.0 for 4:1)
+    #[arg(long, default_value_t = 4.0)]
+    pub ratio: f32,
+
+    /// Knee width in dB (0 for a hard knee)
+    #[arg(long, default_value_t = 6.0)]
+    pub knee: f32,
+
+    /// Attack time in milliseconds
+    #[arg(long, default_value_t = 10.0)]
+    pub attack: f32,
+
+    /// Release time in milliseconds
+    #[arg(long, default_value_t = 120.0)]
+    pub release: f32,
+
+    /// Makeup gain in dB
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    pub makeup: f32,
+
+    /// Level detector (rms, peak)
+    #[arg(long, default_value = "rms")]
+    pub detector: String,
+
+    /// Process channels independently instead of stereo-linked
+    #[arg(long)]
+    pub unlinked: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Applies a lookahead brickwall limiter to a WAV file", long_about = None)]
+pub struct LimitArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Ceiling in dBFS
+    #[arg(long, default_value_t = -0.3, allow_hyphen_values = true)]
+    pub ceiling: f32,
+
+    /// Gain in dB applied before limiting
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    pub drive: f32,
+
+    /// Lookahead in milliseconds
+    #[arg(long, default_value_t = 5.0)]
+    pub lookahead: f32,
+
+    /// Release time in milliseconds
+    #[arg(long, default_value_t = 80.0)]
+    pub release: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+
+    #[test]
+    fn test_gain_computer() {
+        assert_eq!(gain_computer(-30.0, -20.0, 4.0, 0.0), 0.0, "No reduction below threshold");
+        assert!((gain_computer(-12.0, -20.0, 4.0, 0.0) + 6.0).abs() < 1e-5, "8 dB over at 4:1 should reduce by 6 dB");
+        let soft = gain_computer(-20.0, -20.0, 4.0, 6.0);
+        assert!(soft < 0.0 && soft > -1.0, "Soft knee should reduce slightly at the threshold");
+    }
+
+    #[test]
+    fn test_compress_reduces_peaks() {
+        let input_wav = get_dummy();
+        let settings = CompressorSettings { threshold_db: -30.0, ratio: 8.0, attack_ms: 1.0, ..Default::default() };
+
+        let output_wav = compress(&input_wav, &settings).expect("compress function failed");
+
+        let (input_samples, _) = get_samples(&input_wav).unwrap();
+        let (output_samples, _) = get_samples(&output_wav).unwrap();
+        let input_peak = input_samples.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+        let output_peak = output_samples.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+
+        assert_eq!(len(&input_wav).unwrap(), len(&output_wav).unwrap(), "Compression should not change the duration");
+        assert!(output_peak < input_peak, "Compression should lower the peaks");
+    }
+
+    #[test]
+    fn test_limit_respects_ceiling() {
+        let input_wav = get_dummy();
+        let ceiling_db = -1.0;
+
+        let output_wav = limit(&input_wav, 12.0, ceiling_db, 5.0, 80.0).expect("limit function failed");
+
+        let (output_samples, _) = get_samples(&output_wav).unwrap();
+        let output_peak = output_samples.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+        assert!(output_peak <= db_to_amplitude(ceiling_db) + 1e-6, "Limiter output should not exceed the ceiling");
+    }
+
+    #[test]
+    fn test_forward_minimum() {
+        let values = [1.0, 0.5, 1.0, 1.0, 0.2, 1.0];
+        assert_eq!(forward_minimum(&values, 2), vec![0.5, 0.5, 0.2, 0.2, 0.2, 1.0]);
+    }
+}
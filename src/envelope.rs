@@ -0,0 +1,249 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::{get_samples, wrap_samples};
+use crate::dynamics::{Detector, EnvelopeFollower, LevelDetector};
+
+// Floor of the target envelope when flattening, so silence is not blown up
+const FLATTEN_FLOOR: f32 = 1e-3;
+// Largest boost applied while flattening (+24 dB)
+const FLATTEN_MAX_GAIN: f32 = 15.85;
+
+#[derive(Clone, Copy, Debug)]
+pub struct EnvelopeSettings {
+    pub detector: Detector,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+}
+
+impl Default for EnvelopeSettings {
+    fn default() -> Self {
+        EnvelopeSettings { detector: Detector::Peak, attack_ms: 5.0, release_ms: 100.0 }
+    }
+}
+
+// Amplitude envelope of a WAV, one value per frame (channels are linked)
+pub fn envelope(input_wav: &[u8], settings: &EnvelopeSettings) -> Result<(Vec<f32>, u32), String> {
+    let (samples, spec) = get_samples(input_wav)?;
+    let channels = spec.channels as usize;
+    let sample_rate = spec.sample_rate as f32;
+
+    let mut detectors: Vec<LevelDetector> = (0..channels).map(|_| LevelDetector::new(settings.detector, sample_rate)).collect();
+    let mut follower = EnvelopeFollower::new(settings.attack_ms, settings.release_ms, sample_rate);
+
+    let values = samples
+        .chunks(channels)
+        .map(|frame| {
+            let level = frame
+                .iter()
+                .zip(detectors.iter_mut())
+                .fold(0.0f32, |acc, (&s, detector)| acc.max(detector.process(s)));
+            follower.process(level)
+        })
+        .collect();
+
+    Ok((values, spec.sample_rate))
+}
+
+// Envelope reduced to (time in seconds, level) points every `resolution_ms`
+pub fn envelope_points(values: &[f32], sample_rate: u32, resolution_ms: f32) -> Vec<(f32, f32)> {
+    let step = ((resolution_ms / 1000.0 * sample_rate as f32) as usize).max(1);
+    values
+        .iter()
+        .enumerate()
+        .step_by(step)
+        .map(|(i, &level)| (i as f32 / sample_rate as f32, level))
+        .collect()
+}
+
+pub fn envelope_to_csv(points: &[(f32, f32)]) -> String {
+    let mut csv = String::from("time,level\n");
+    for (time, level) in points {
+        csv.push_str(&format!("{:.4},{:.6}\n", time, level));
+    }
+    csv
+}
+
+pub fn envelope_to_json(points: &[(f32, f32)]) -> String {
+    let entries: Vec<String> = points
+        .iter()
+        .map(|(time, level)| format!("{{\"time\":{:.4},\"level\":{:.6}}}", time, level))
+        .collect();
+    format!("[{}]", entries.join(","))
+}
+
+// Imposes the amplitude contour of `source_wav` on `target_wav`. The source
+// envelope loops if it is shorter. With `flatten`, the target's own dynamics
+// are evened out first so only the source contour remains.
+pub fn envelope_transfer(source_wav: &[u8], target_wav: &[u8], settings: &EnvelopeSettings, flatten: bool) -> Result<Vec<u8>, String> {
+    let (source_envelope, source_rate) = envelope(source_wav, settings)?;
+    let (target_envelope, target_rate) = envelope(target_wav, settings)?;
+    if source_rate != target_rate {
+        return Err("Sample rates do not match.".to_string());
+    }
+
+    let source_peak = source_envelope.iter().fold(0.0f32, |acc, &v| acc.max(v));
+    let target_peak = target_envelope.iter().fold(0.0f32, |acc, &v| acc.max(v));
+    if source_envelope.is_empty() || source_peak == 0.0 {
+        return Err("Source is silent, there is no envelope to transfer.".to_string());
+    }
+
+    let (mut samples, spec) = get_samples(target_wav)?;
+    let channels = spec.channels as usize;
+
+    for (i, frame) in samples.chunks_mut(channels).enumerate() {
+        let mut gain = source_envelope[i % source_envelope.len()] / source_peak;
+        if flatten {
+            gain *= (target_peak / target_envelope[i].max(FLATTEN_FLOOR)).min(FLATTEN_MAX_GAIN);
+        }
+        for sample in frame.iter_mut() {
+            *sample *= gain;
+        }
+    }
+
+    wrap_samples(samples, spec)
+}
+
+#[wasm_bindgen]
+pub fn envelope_js(input_wav: &[u8], detector: &str, attack_ms: f32, release_ms: f32) -> Result<Vec<f32>, JsValue> {
+    let result = detector.parse::<Detector>().and_then(|detector| {
+        let settings = EnvelopeSettings { detector, attack_ms, release_ms };
+        envelope(input_wav, &settings).map(|(values, _)| values)
+    });
+    result.map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn envelope_json_js(input_wav: &[u8], detector: &str, attack_ms: f32, release_ms: f32, resolution_ms: f32) -> Result<String, JsValue> {
+    let result = detector.parse::<Detector>().and_then(|detector| {
+        let settings = EnvelopeSettings { detector, attack_ms, release_ms };
+        let (values, sample_rate) = envelope(input_wav, &settings)?;
+        Ok(envelope_to_json(&envelope_points(&values, sample_rate, resolution_ms)))
+    });
+    result.map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn envelope_transfer_js(
+    source_wav: &[u8],
+    target_wav: &[u8],
+    detector: &str,
+    attack_ms: f32,
+    release_ms: f32,
+    flatten: bool,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = detector.parse::<Detector>().and_then(|detector| {
+        let settings = EnvelopeSettings { detector, attack_ms, release_ms };
+        envelope_transfer(source_wav, target_wav, &settings, flatten)
+    });
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Extracts the amplitude envelope of a WAV file", long_about = None)]
+pub struct EnvelopeArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Level detector (peak, rms)
+    #[arg(long, default_value = "peak")]
+    pub detector: String,
+
+    /// Attack time in milliseconds
+    #[arg(long, default_value_t = 5.0)]
+    pub attack: f32,
+
+    /// Release time in milliseconds
+    #[arg(long, default_value_t = 100.0)]
+    pub release: f32,
+
+    /// Time between exported points in milliseconds
+    #[arg(long, default_value_t = 10.0)]
+    pub resolution: f32,
+
+    /// Write the envelope to a CSV file
+    #[arg(long)]
+    pub csv: Option<String>,
+
+    /// Write the envelope to a JSON file
+    #[arg(long)]
+    pub json: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Imposes the amplitude envelope of one WAV file on another", long_about = None)]
+pub struct TransferArgs {
+    /// Source WAV file (whose envelope is taken)
+    #[arg()]
+    pub source: String,
+
+    /// Target WAV file (which is shaped)
+    #[arg()]
+    pub target: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Level detector (peak, rms)
+    #[arg(long, default_value = "peak")]
+    pub detector: String,
+
+    /// Attack time in milliseconds
+    #[arg(long, default_value_t = 5.0)]
+    pub attack: f32,
+
+    /// Release time in milliseconds
+    #[arg(long, default_value_t = 100.0)]
+    pub release: f32,
+
+    /// Even out the target's own dynamics first
+    #[arg(long)]
+    pub flatten: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+    use hound::{WavSpec, SampleFormat};
+
+    #[test]
+    fn test_envelope_follows_level() {
+        let spec = WavSpec { channels: 1, sample_rate: 1000, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        let mut samples = vec![0.8f32; 500];
+        samples.extend(vec![0.0f32; 500]);
+        let wav = wrap_samples(samples, spec).unwrap();
+
+        let (values, sample_rate) = envelope(&wav, &EnvelopeSettings::default()).unwrap();
+        assert_eq!(sample_rate, 1000);
+        assert!((values[400] - 0.8).abs() < 0.01, "The envelope should settle on the level");
+        assert!(values[999] < 0.01, "The envelope should release after the signal stops");
+
+        let points = envelope_points(&values, sample_rate, 100.0);
+        assert_eq!(points.len(), 10);
+        assert!(envelope_to_csv(&points).starts_with("time,level\n0.0000,"));
+        assert!(envelope_to_json(&points).starts_with("[{\"time\":0.0000,"));
+    }
+
+    #[test]
+    fn test_envelope_transfer() {
+        let spec = WavSpec { channels: 1, sample_rate: 44100, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        let mut gate = vec![0.9f32; 22050];
+        gate.extend(vec![0.0f32; 22050]);
+        let source = wrap_samples(gate, spec).unwrap();
+        let target = get_dummy();
+
+        let settings = EnvelopeSettings { release_ms: 10.0, ..EnvelopeSettings::default() };
+        let output_wav = envelope_transfer(&source, &target, &settings, false).expect("envelope_transfer function failed");
+
+        let (output, _) = get_samples(&output_wav).unwrap();
+        let channels = 2;
+        assert_eq!(len(&target).unwrap(), len(&output_wav).unwrap(), "The target's duration should be kept");
+        assert!(output[40000 * channels..44000 * channels].iter().all(|s| s.abs() < 1e-3), "Silent source parts should silence the target");
+    }
+}
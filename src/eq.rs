@@ -0,0 +1,177 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use std::str::FromStr;
+use crate::utils::{get_samples, wrap_samples};
+use crate::filter::{Biquad, BiquadType};
+
+// Butterworth Q, used when a band leaves it out
+const DEFAULT_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EqBand {
+    pub kind: BiquadType,
+    pub frequency: f32,
+    pub gain_db: f32,
+    pub q: f32,
+}
+
+// Parses a single band: "hp:80", "lp:12000:0.5", "peak:400:-4:1.2", "shelf_hi:8000:+3".
+// Filters take frequency[:q], peaks and shelves take frequency:gain[:q].
+impl FromStr for EqBand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let number = |i: usize| -> Result<Option<f32>, String> {
+            parts
+                .get(i)
+                .map(|p| p.parse::<f32>().map_err(|_| format!("Invalid number '{}' in band '{}'", p, s)))
+                .transpose()
+        };
+
+        let (kind, has_gain) = match parts[0] {
+            "hp" => (BiquadType::Highpass, false),
+            "lp" => (BiquadType::Lowpass, false),
+            "bp" => (BiquadType::Bandpass, false),
+            "notch" => (BiquadType::Notch, false),
+            "peak" => (BiquadType::Peak, true),
+            "shelf_lo" => (BiquadType::LowShelf, true),
+            "shelf_hi" => (BiquadType::HighShelf, true),
+            other => return Err(format!("Band type not recognized: {}", other)),
+        };
+
+        let max_parts = if has_gain { 4 } else { 3 };
+        if parts.len() > max_parts {
+            return Err(format!("Too many values in band '{}'", s));
+        }
+
+        let frequency = number(1)?.ok_or_else(|| format!("Missing frequency in band '{}'", s))?;
+        let (gain_db, q) = if has_gain {
+            let gain = number(2)?.ok_or_else(|| format!("Missing gain in band '{}'", s))?;
+            (gain, number(3)?)
+        } else {
+            (0.0, number(2)?)
+        };
+
+        Ok(EqBand { kind, frequency, gain_db, q: q.unwrap_or(DEFAULT_Q) })
+    }
+}
+
+// Parses a whitespace-separated list of bands
+pub fn parse_bands(spec: &str) -> Result<Vec<EqBand>, String> {
+    let bands = spec.split_whitespace().map(str::parse).collect::<Result<Vec<EqBand>, String>>()?;
+    if bands.is_empty() {
+        return Err("No EQ bands given.".to_string());
+    }
+    Ok(bands)
+}
+
+fn build_filters(bands: &[EqBand], sample_rate: f32) -> Result<Vec<Biquad>, String> {
+    bands
+        .iter()
+        .map(|b| Biquad::new(b.kind, b.frequency, b.gain_db, b.q, sample_rate))
+        .collect()
+}
+
+pub fn eq(input_wav: &[u8], bands: &[EqBand]) -> Result<Vec<u8>, String> {
+    let (mut samples, spec) = get_samples(input_wav)?;
+    let channels = spec.channels as usize;
+
+    let filters = build_filters(bands, spec.sample_rate as f32)?;
+    let mut chains: Vec<Vec<Biquad>> = vec![filters; channels];
+
+    for frame in samples.chunks_mut(channels) {
+        for (sample, chain) in frame.iter_mut().zip(chains.iter_mut()) {
+            *sample = chain.iter_mut().fold(*sample, |x, filter| filter.process(x));
+        }
+    }
+
+    wrap_samples(samples, spec)
+}
+
+// Combined magnitude response of all bands in dB, at each of `frequencies`
+pub fn frequency_response(bands: &[EqBand], sample_rate: f32, frequencies: &[f32]) -> Result<Vec<f32>, String> {
+    let filters = build_filters(bands, sample_rate)?;
+    Ok(frequencies
+        .iter()
+        .map(|&f| filters.iter().map(|filter| filter.response_db(f, sample_rate)).sum())
+        .collect())
+}
+
+// Third-octave centres from 20 Hz to 20 kHz
+pub fn third_octave_frequencies() -> Vec<f32> {
+    (-17..=13).map(|k| 1000.0 * 2.0f32.powf(k as f32 / 3.0)).collect()
+}
+
+#[wasm_bindgen]
+pub fn eq_js(input_wav: &[u8], spec: &str) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = parse_bands(spec).and_then(|bands| eq(input_wav, &bands));
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+// Response of a band spec at the given frequencies, e.g. to draw an EQ curve
+#[wasm_bindgen]
+pub fn eq_response_js(spec: &str, sample_rate: f32, frequencies: Vec<f32>) -> Result<Vec<f32>, JsValue> {
+    parse_bands(spec)
+        .and_then(|bands| frequency_response(&bands, sample_rate, &frequencies))
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Applies a multi-band parametric EQ to a WAV file", long_about = None)]
+pub struct EqArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Bands, e.g. "hp:80 peak:400:-4:1.2 shelf_hi:8000:+3"
+    /// (types: hp, lp, bp, notch take freq[:q]; peak, shelf_lo, shelf_hi take freq:gain[:q])
+    #[arg(allow_hyphen_values = true)]
+    pub bands: String,
+
+    /// Also print the combined frequency response
+    #[arg(short, long)]
+    pub analyze: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+
+    #[test]
+    fn test_parse_bands() {
+        let bands = parse_bands("hp:80 peak:400:-4:1.2 shelf_hi:8000:+3").unwrap();
+        assert_eq!(bands.len(), 3);
+        assert_eq!(bands[0], EqBand { kind: BiquadType::Highpass, frequency: 80.0, gain_db: 0.0, q: DEFAULT_Q });
+        assert_eq!(bands[1], EqBand { kind: BiquadType::Peak, frequency: 400.0, gain_db: -4.0, q: 1.2 });
+        assert_eq!(bands[2].gain_db, 3.0);
+
+        assert!(parse_bands("peak:400").is_err(), "Peaks need a gain");
+        assert!(parse_bands("wobble:400").is_err(), "Unknown band types should be rejected");
+        assert!(parse_bands("hp:80:0.7:3").is_err(), "Filters do not take a gain");
+    }
+
+    #[test]
+    fn test_eq_effect() {
+        let input_wav = get_dummy();
+        let bands = parse_bands("hp:80 peak:400:-4:1.2 shelf_hi:8000:+3").unwrap();
+
+        let output_wav = eq(&input_wav, &bands).expect("eq function failed");
+
+        assert_eq!(len(&input_wav).unwrap(), len(&output_wav).unwrap(), "EQ should not change the duration");
+        assert_ne!(input_wav, output_wav, "EQ should modify the audio content");
+
+        let response = frequency_response(&bands, 44100.0, &[400.0, 1000.0]).unwrap();
+        assert!(response[0] < -3.5, "The cut at 400 Hz should show in the response");
+        assert!(response[1].abs() < 1.5, "1 kHz should be nearly untouched");
+    }
+}
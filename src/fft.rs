@@ -0,0 +1,163 @@
+use std::f32::consts::PI;
+use std::ops::{Add, Mul, Sub};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex {
+    pub fn new(re: f32, im: f32) -> Self {
+        Complex { re, im }
+    }
+
+    pub fn from_polar(magnitude: f32, phase: f32) -> Self {
+        Complex::new(magnitude * phase.cos(), magnitude * phase.sin())
+    }
+
+    pub fn norm(&self) -> f32 {
+        (self.re * self.re + self.im * self.im).sqrt()
+    }
+
+    pub fn arg(&self) -> f32 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn conj(&self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Mul<f32> for Complex {
+    type Output = Complex;
+    fn mul(self, scale: f32) -> Complex {
+        Complex::new(self.re * scale, self.im * scale)
+    }
+}
+
+// In-place iterative radix-2 FFT. The buffer length must be a power of two.
+// The inverse transform is scaled by 1/n so that fft followed by ifft is lossless.
+pub fn fft(buffer: &mut [Complex]) {
+    transform(buffer, false);
+}
+
+pub fn ifft(buffer: &mut [Complex]) {
+    transform(buffer, true);
+    let scale = 1.0 / buffer.len() as f32;
+    for value in buffer.iter_mut() {
+        *value = *value * scale;
+    }
+}
+
+// Forward FFT of a real signal, zero-padded (or truncated) to `size`
+pub fn real_fft(samples: &[f32], size: usize) -> Vec<Complex> {
+    let mut buffer: Vec<Complex> = samples
+        .iter()
+        .take(size)
+        .map(|&s| Complex::new(s, 0.0))
+        .collect();
+    buffer.resize(size, Complex::default());
+    fft(&mut buffer);
+    buffer
+}
+
+pub fn next_pow2(n: usize) -> usize {
+    n.max(1).next_power_of_two()
+}
+
+fn transform(buffer: &mut [Complex], inverse: bool) {
+    let n = buffer.len();
+    if n <= 1 {
+        return;
+    }
+    assert!(n.is_power_of_two(), "FFT size must be a power of two");
+
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            buffer.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * PI / len as f32;
+        let step = Complex::from_polar(1.0, angle);
+        for start in (0..n).step_by(len) {
+            let mut w = Complex::new(1.0, 0.0);
+            for k in 0..len / 2 {
+                let even = buffer[start + k];
+                let odd = buffer[start + k + len / 2] * w;
+                buffer[start + k] = even + odd;
+                buffer[start + k + len / 2] = even - odd;
+                w = w * step;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fft_roundtrip() {
+        let signal: Vec<f32> = (0..64).map(|i| (i as f32 * 0.3).sin() + 0.25).collect();
+        let mut buffer = real_fft(&signal, 64);
+        ifft(&mut buffer);
+
+        for (original, restored) in signal.iter().zip(buffer.iter()) {
+            assert!((original - restored.re).abs() < 1e-4, "Roundtrip should restore the signal");
+            assert!(restored.im.abs() < 1e-4, "Roundtrip of a real signal should be real");
+        }
+    }
+
+    #[test]
+    fn test_fft_finds_sine_bin() {
+        let n = 256;
+        let bin = 10;
+        let signal: Vec<f32> = (0..n)
+            .map(|i| (2.0 * PI * bin as f32 * i as f32 / n as f32).sin())
+            .collect();
+        let spectrum = real_fft(&signal, n);
+
+        let peak = (0..n / 2)
+            .max_by(|&a, &b| spectrum[a].norm().partial_cmp(&spectrum[b].norm()).unwrap())
+            .unwrap();
+        assert_eq!(peak, bin, "Peak should be at the sine's bin");
+    }
+}
@@ -0,0 +1,276 @@
+use std::f32::consts::PI;
+
+// Streaming one-pole filters, same formulas as the lowpass/highpass effects,
+// for use inside other effects (feedback loops, sidechains, envelopes...)
+
+pub struct OnePoleLowpass {
+    alpha: f32,
+    y_prev: f32,
+}
+
+impl OnePoleLowpass {
+    pub fn new(cutoff_frequency: f32, sample_rate: f32) -> Self {
+        let alpha = (2.0 * PI * cutoff_frequency) / (sample_rate + (2.0 * PI * cutoff_frequency));
+        OnePoleLowpass { alpha, y_prev: 0.0 }
+    }
+
+    pub fn process(&mut self, x: f32) -> f32 {
+        self.y_prev = self.alpha * x + (1.0 - self.alpha) * self.y_prev;
+        self.y_prev
+    }
+}
+
+pub struct OnePoleHighpass {
+    alpha: f32,
+    gain_compensation: f32,
+    x_prev: f32,
+    y_prev: f32,
+}
+
+impl OnePoleHighpass {
+    pub fn new(cutoff_frequency: f32, sample_rate: f32) -> Self {
+        let alpha = 1.0 / (1.0 + (sample_rate / (2.0 * PI * cutoff_frequency)));
+        let gain_compensation = 1.0 / ((1.0 + alpha) / 2.0);
+        OnePoleHighpass { alpha, gain_compensation, x_prev: 0.0, y_prev: 0.0 }
+    }
+
+    pub fn process(&mut self, x: f32) -> f32 {
+        let y = self.alpha * (self.y_prev + x - self.x_prev) * self.gain_compensation;
+        self.y_prev = y;
+        self.x_prev = x;
+        y
+    }
+}
+
+// Optional high-cut/low-cut pair, skipped when the cutoff is not set
+pub struct BandLimiter {
+    lowcut: Option<OnePoleHighpass>,
+    highcut: Option<OnePoleLowpass>,
+}
+
+impl BandLimiter {
+    pub fn new(lowcut_hz: Option<f32>, highcut_hz: Option<f32>, sample_rate: f32) -> Self {
+        BandLimiter {
+            lowcut: lowcut_hz.filter(|&f| f > 0.0).map(|f| OnePoleHighpass::new(f, sample_rate)),
+            highcut: highcut_hz.filter(|&f| f > 0.0).map(|f| OnePoleLowpass::new(f, sample_rate)),
+        }
+    }
+
+    pub fn process(&mut self, mut x: f32) -> f32 {
+        if let Some(hp) = self.lowcut.as_mut() {
+            x = hp.process(x);
+        }
+        if let Some(lp) = self.highcut.as_mut() {
+            x = lp.process(x);
+        }
+        x
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BiquadType {
+    Lowpass,
+    Highpass,
+    Bandpass,
+    Notch,
+    Peak,
+    LowShelf,
+    HighShelf,
+}
+
+// Second-order IIR section with the RBJ "Audio EQ Cookbook" coefficients,
+// normalized so a0 = 1. Runs in f64 to stay stable at low frequencies.
+#[derive(Clone, Debug)]
+pub struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    pub fn new(kind: BiquadType, frequency: f32, gain_db: f32, q: f32, sample_rate: f32) -> Result<Self, String> {
+        if frequency <= 0.0 || frequency >= sample_rate / 2.0 {
+            return Err(format!("Frequency {} Hz must be between 0 and the Nyquist frequency.", frequency));
+        }
+        if q <= 0.0 {
+            return Err("Q must be positive.".to_string());
+        }
+
+        let w0 = 2.0 * std::f64::consts::PI * frequency as f64 / sample_rate as f64;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q as f64);
+        let a = 10.0f64.powf(gain_db as f64 / 40.0);
+        let shelf = 2.0 * a.sqrt() * alpha;
+
+        let (b0, b1, b2, a0, a1, a2) = match kind {
+            BiquadType::Lowpass => ((1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            BiquadType::Highpass => ((1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            BiquadType::Bandpass => (alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            BiquadType::Notch => (1.0, -2.0 * cos, 1.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            BiquadType::Peak => (1.0 + alpha * a, -2.0 * cos, 1.0 - alpha * a, 1.0 + alpha / a, -2.0 * cos, 1.0 - alpha / a),
+            BiquadType::LowShelf => (
+                a * ((a + 1.0) - (a - 1.0) * cos + shelf),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                a * ((a + 1.0) - (a - 1.0) * cos - shelf),
+                (a + 1.0) + (a - 1.0) * cos + shelf,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                (a + 1.0) + (a - 1.0) * cos - shelf,
+            ),
+            BiquadType::HighShelf => (
+                a * ((a + 1.0) + (a - 1.0) * cos + shelf),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - shelf),
+                (a + 1.0) - (a - 1.0) * cos + shelf,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - shelf,
+            ),
+        };
+
+        Ok(Biquad::from_coefficients(b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0))
+    }
+
+    // Filter with coefficients already normalized by a0
+    pub fn from_coefficients(b0: f64, b1: f64, b2: f64, a1: f64, a2: f64) -> Self {
+        Biquad { b0, b1, b2, a1, a2, z1: 0.0, z2: 0.0 }
+    }
+
+    // Transposed direct form II
+    pub fn process(&mut self, x: f32) -> f32 {
+        let x = x as f64;
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y as f32
+    }
+
+    // Magnitude response in dB at `frequency`
+    pub fn response_db(&self, frequency: f32, sample_rate: f32) -> f32 {
+        let w = 2.0 * std::f64::consts::PI * frequency as f64 / sample_rate as f64;
+        let (s1, c1) = w.sin_cos();
+        let (s2, c2) = (2.0 * w).sin_cos();
+        let num_re = self.b0 + self.b1 * c1 + self.b2 * c2;
+        let num_im = -(self.b1 * s1 + self.b2 * s2);
+        let den_re = 1.0 + self.a1 * c1 + self.a2 * c2;
+        let den_im = -(self.a1 * s1 + self.a2 * s2);
+        let magnitude = ((num_re * num_re + num_im * num_im) / (den_re * den_re + den_im * den_im)).sqrt();
+        (20.0 * magnitude.max(1e-12).log10()) as f32
+    }
+}
+
+// Taps of the oversampling FIR per phase of the polyphase filter
+const OVERSAMPLING_TAPS_PER_PHASE: usize = 16;
+
+// Blackman-windowed sinc lowpass at the original Nyquist frequency, for a
+// signal running `factor` times faster. Odd length so the delay is whole.
+fn oversampling_kernel(factor: usize) -> Vec<f32> {
+    let taps = OVERSAMPLING_TAPS_PER_PHASE * factor + 1;
+    let center = (taps / 2) as f32;
+    (0..taps)
+        .map(|n| {
+            let t = (n as f32 - center) / factor as f32;
+            let sinc = if t == 0.0 { 1.0 } else { (PI * t).sin() / (PI * t) };
+            let w = 2.0 * PI * n as f32 / (taps - 1) as f32;
+            let blackman = 0.42 - 0.5 * w.cos() + 0.08 * (2.0 * w).cos();
+            sinc * blackman
+        })
+        .collect()
+}
+
+// Band-limited interpolation of a single channel by an integer factor
+pub fn upsample(signal: &[f32], factor: usize) -> Vec<f32> {
+    if factor <= 1 {
+        return signal.to_vec();
+    }
+    let kernel = oversampling_kernel(factor);
+    let delay = kernel.len() / 2;
+    let output_len = signal.len() * factor;
+
+    (0..output_len)
+        .map(|m| {
+            // Only every factor-th tap meets a non-zero (stuffed) input sample
+            let position = m + delay;
+            let mut acc = 0.0;
+            let mut k = position % factor;
+            while k < kernel.len() && k <= position {
+                if let Some(&x) = signal.get((position - k) / factor) {
+                    acc += kernel[k] * x;
+                }
+                k += factor;
+            }
+            acc
+        })
+        .collect()
+}
+
+// Band-limited decimation of a single channel by an integer factor
+pub fn downsample(signal: &[f32], factor: usize) -> Vec<f32> {
+    if factor <= 1 {
+        return signal.to_vec();
+    }
+    let kernel = oversampling_kernel(factor);
+    let delay = kernel.len() / 2;
+    let output_len = signal.len() / factor;
+
+    (0..output_len)
+        .map(|n| {
+            let position = n * factor + delay;
+            let mut acc = 0.0;
+            for (k, &h) in kernel.iter().enumerate() {
+                if let Some(&x) = position.checked_sub(k).and_then(|i| signal.get(i)) {
+                    acc += h * x;
+                }
+            }
+            acc / factor as f32
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    #[test]
+    fn test_biquad_responses() {
+        let sr = 48000.0;
+        let peak = Biquad::new(BiquadType::Peak, 1000.0, -6.0, 1.0, sr).unwrap();
+        assert!((peak.response_db(1000.0, sr) + 6.0).abs() < 1e-3, "Peak should reach its gain at the centre");
+        assert!(peak.response_db(50.0, sr).abs() < 0.1, "Peak should leave distant frequencies alone");
+
+        let highpass = Biquad::new(BiquadType::Highpass, 1000.0, 0.0, FRAC_1_SQRT_2, sr).unwrap();
+        assert!((highpass.response_db(1000.0, sr) + 3.01).abs() < 0.05, "Butterworth highpass is -3 dB at the cutoff");
+
+        let shelf = Biquad::new(BiquadType::HighShelf, 2000.0, 6.0, FRAC_1_SQRT_2, sr).unwrap();
+        assert!((shelf.response_db(20000.0, sr) - 6.0).abs() < 0.2, "High shelf should boost the top end");
+
+        assert!(Biquad::new(BiquadType::Peak, 30000.0, 0.0, 1.0, sr).is_err(), "Frequencies above Nyquist should be rejected");
+    }
+
+    #[test]
+    fn test_oversampling_roundtrip() {
+        let signal: Vec<f32> = (0..2000).map(|i| (2.0 * PI * 440.0 * i as f32 / 44100.0).sin() * 0.5).collect();
+
+        for factor in [2, 4, 8] {
+            let up = upsample(&signal, factor);
+            assert_eq!(up.len(), signal.len() * factor);
+            let down = downsample(&up, factor);
+
+            // Ignore the filter's edges
+            for i in 100..1900 {
+                assert!((down[i] - signal[i]).abs() < 1e-2, "Roundtrip at {}x should preserve the signal", factor);
+            }
+        }
+    }
+
+    #[test]
+    fn test_upsample_interpolates() {
+        let signal: Vec<f32> = (0..1000).map(|i| (2.0 * PI * 100.0 * i as f32 / 8000.0).sin()).collect();
+        let up = upsample(&signal, 4);
+        let expected = (2.0 * PI * 100.0 * 500.5 / 8000.0).sin();
+        assert!((up[500 * 4 + 2] - expected).abs() < 1e-2, "Upsampled points should fall on the original waveform");
+    }
+}
@@ -2,6 +2,8 @@ use wasm_bindgen::prelude::*;
 use js_sys;
 use clap::Parser;
 use crate::utils::{get_samples,wrap_samples, clamp_samples};
+use crate::delay::DelayLine;
+use crate::lfo::{Lfo, Waveform};
 
 pub fn flanger(
     input_wav: &[u8],
@@ -9,59 +11,47 @@ pub fn flanger(
     depth_ms: f32, // Depth of modulation in milliseconds
     rate_hz: f32,  // LFO rate in Hz
     feedback: f32, // Feedback amount (-1.0 to 1.0)
+) -> Result<Vec<u8>, String> {
+    flanger_with_waveform(input_wav, delay_ms, depth_ms, rate_hz, feedback, Waveform::Sine)
+}
+
+pub fn flanger_with_waveform(
+    input_wav: &[u8],
+    delay_ms: f32,
+    depth_ms: f32,
+    rate_hz: f32,
+    feedback: f32,
+    waveform: Waveform, // LFO shape
 ) -> Result<Vec<u8>, String> {
     let (samples, spec) = get_samples(input_wav)?;
 
     let sample_rate = spec.sample_rate as f32;
     let channels = spec.channels as usize;
-    let num_samples = samples.len();
 
     // Max delay in samples, ensuring enough space for modulation
     let max_delay_samples = (((delay_ms + depth_ms) / 1000.0 * sample_rate) as usize).max(1);
 
-    let mut delay_line = vec![0.0; max_delay_samples * channels];
-    let mut write_pointer = 0;
-
-    let mut lfo_phase: f32 = 0.0;
-    let lfo_increment = 2.0 * std::f32::consts::PI * rate_hz / sample_rate;
+    let mut delay_lines: Vec<DelayLine> = (0..channels).map(|_| DelayLine::new(max_delay_samples)).collect();
+    let mut lfos: Vec<Lfo> = (0..channels).map(|_| Lfo::new(waveform, rate_hz, sample_rate)).collect();
 
-    let mut output_samples = Vec::with_capacity(num_samples);
+    let mut output_samples = Vec::with_capacity(samples.len());
 
-    for i in 0..num_samples {
-        let current_input_sample = samples[i];
+    for frame in samples.chunks(channels) {
+        for (ch, &current_input_sample) in frame.iter().enumerate() {
+            // Calculate modulated delay for current sample
+            let modulated_delay_ms = delay_ms + depth_ms * lfos[ch].next_unipolar();
+            let modulated_delay_samples = modulated_delay_ms / 1000.0 * sample_rate;
 
-        // Calculate modulated delay for current sample
-        let modulated_delay_ms = delay_ms + (depth_ms * (lfo_phase.sin() * 0.5 + 0.5));
-        let modulated_delay_samples_float = modulated_delay_ms / 1000.0 * sample_rate;
-
-        // Read pointer for delay line (circular buffer)
-        let read_pointer_float = (write_pointer as f32 - modulated_delay_samples_float).rem_euclid(max_delay_samples as f32);
-        
-        let read_pointer_floor = read_pointer_float.floor() as usize;
-        let read_pointer_ceil = read_pointer_float.ceil() as usize;
-        let frac = read_pointer_float.fract();
-
-        let delayed_sample;
-        if max_delay_samples > 0 {
-            let sample1 = delay_line[read_pointer_floor];
-            let sample2 = delay_line[read_pointer_ceil % max_delay_samples]; // Ensure wrap-around
-            delayed_sample = sample1 * (1.0 - frac) + sample2 * frac;
-        } else {
-            delayed_sample = 0.0;
-        }
+            let delayed_sample = delay_lines[ch].read(modulated_delay_samples);
 
-        // Flanger equation
-        let flanged_sample = current_input_sample + delayed_sample + feedback * delayed_sample; // Simplified feedback
+            // Flanger equation
+            let flanged_sample = current_input_sample + delayed_sample + feedback * delayed_sample; // Simplified feedback
 
-        // Write current sample to delay line
-        delay_line[write_pointer] = current_input_sample + feedback * delayed_sample; // Feedback into delay line
+            // Feedback into delay line
+            delay_lines[ch].write(current_input_sample + feedback * delayed_sample);
 
-        // Advance write pointer (circular)
-        write_pointer = (write_pointer + 1) % max_delay_samples;
-
-        output_samples.push(flanged_sample);
-
-        lfo_phase += lfo_increment;
+            output_samples.push(flanged_sample);
+        }
     }
 
     clamp_samples(&mut output_samples); // Add soft clipping here
@@ -75,8 +65,14 @@ pub fn flanger_js(
     depth_ms: f32,
     rate_hz: f32,
     feedback: f32,
+    waveform: Option<String>,
 ) -> Result<js_sys::Uint8Array, JsValue> {
-    match flanger(input_wav, delay_ms, depth_ms, rate_hz, feedback) {
+    let result = waveform
+        .as_deref()
+        .unwrap_or("sine")
+        .parse::<Waveform>()
+        .and_then(|waveform| flanger_with_waveform(input_wav, delay_ms, depth_ms, rate_hz, feedback, waveform));
+    match result {
         Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
         Err(e) => Err(JsValue::from_str(&e)),
     }
@@ -106,8 +102,12 @@ pub struct FlangerArgs {
     pub rate: f32,
 
     /// Feedback amount (-1.0 to 1.0)
-    #[arg(default_value_t = 0.0)]
+    #[arg(default_value_t = 0.0, allow_hyphen_values = true)]
     pub feedback: f32,
+
+    /// LFO waveform (sine, triangle, square, saw, random)
+    #[arg(long, default_value = "sine")]
+    pub waveform: String,
 }
 
 #[cfg(test)]
@@ -138,4 +138,14 @@ mod tests {
         // Assert that the content has changed (i.e., flanger was applied)
         assert_ne!(input_wav, output_wav, "Flanger should modify the audio content");
     }
+
+    #[test]
+    fn test_flanger_waveforms_differ() {
+        let input_wav = get_dummy();
+
+        let sine = flanger_with_waveform(&input_wav, 2.0, 3.0, 1.0, 0.3, Waveform::Sine).unwrap();
+        let square = flanger_with_waveform(&input_wav, 2.0, 3.0, 1.0, 0.3, Waveform::Square).unwrap();
+
+        assert_ne!(sine, square, "Different LFO waveforms should produce different results");
+    }
 }
@@ -1,7 +1,19 @@
 use rand::rngs::StdRng;
 use rand::prelude::SliceRandom;
 use rand::Rng;
-use crate::{bitcrush, flanger, highpass, lowpass, reverb, reverse, overdrive, speed};
+use crate::{bitcrush, restretch, highpass, lowpass, reverb, reverse, speed, delay, chorus, phaser, tremolo, autopan, ringmod, tapestop};
+use crate::flanger::flanger_with_waveform;
+use crate::gate::{gate, GateSettings};
+use crate::overdrive::{get_curve_list, overdrive_with_settings, OverdriveSettings};
+use crate::lfo::{get_waveform_list, Waveform};
+use crate::tape::{tape, TapeSettings};
+use crate::vinyl::{vinyl, VinylSettings};
+use crate::stutter::{stutter, StutterSettings};
+use crate::spectral::{spectral_freeze, spectral_blur, spectral_gate, frequency_shift, spectral_scramble};
+use crate::stft::StftSettings;
+use crate::time::note_to_seconds;
+use crate::transient::{transient, TransientSettings};
+use crate::bpm::{bpm, MIN_CONFIDENCE};
 
 pub fn apply_fx_with_rng(wav:&[u8], rng: &mut StdRng, mut fx: String) -> Result<Vec<u8>,String> {
 
@@ -20,21 +32,171 @@ pub fn apply_fx_with_rng(wav:&[u8], rng: &mut StdRng, mut fx: String) -> Result<
     match fx.as_str() {
         "highpass" => highpass(wav, rng.gen_range(4000.0..=5000.0)),
         "lowpass" => lowpass(wav, rng.gen_range(300.0..=999.0)),
-        "bitcrush" => bitcrush(wav, rng.gen_range(1.0..=15.0)),
-        "reverb" => reverb(wav, rng.gen_range(1..=250), rng.gen_range(0.1..=0.9)),
-        "reverse" => reverse(wav),
-        "overdrive" => overdrive(
+        "bitcrush" => bitcrush(
             wav,
-            rng.gen_range(20.0..=50.0), 
-            -rng.gen_range(1.0..=5.0)
+            rng.gen_range(2.0..=8.0),
+            rng.gen_range(1.0..=12.0),
+            rng.gen_bool(0.5),
+            rng.gen_bool(0.3)
         ),
+        "restretch" => restretch(wav, rng.gen_range(1.0..=15.0)),
+        "reverb" => reverb(wav, rng.gen_range(1..=250), rng.gen_range(0.1..=0.9)),
+        "reverse" => reverse(wav),
+        "overdrive" => {
+            let settings = OverdriveSettings {
+                curve: get_curve_list().choose(rng).unwrap().parse()?,
+                gain_db: rng.gen_range(20.0..=50.0),
+                output_gain_db: -rng.gen_range(1.0..=5.0),
+                pre_lowcut: Some(rng.gen_range(60.0..=200.0)),
+                post_highcut: Some(rng.gen_range(3000.0..=8000.0)),
+                mix: rng.gen_range(0.6..=1.0),
+                oversample: 4,
+            };
+
+            overdrive_with_settings(wav, &settings)
+        },
         "flanger" => {  
             let delay_ms = rng.gen_range(0.1..=0.6);
             let depth_ms = rng.gen_range(0.1..=9.99);
             let rate_hz  = rng.gen_range(6.666..=666.0);
             let feedback = 0.0;
+            let waveform = get_rand_waveform(rng)?;
+
+            flanger_with_waveform(wav, delay_ms, depth_ms, rate_hz, feedback, waveform)
+        },
+        "chorus" => {
+            let voices = rng.gen_range(2..=5);
+            let delay_ms = rng.gen_range(10.0..=30.0);
+            let depth_ms = rng.gen_range(1.0..=8.0);
+            let rate_hz = rng.gen_range(0.1..=2.0);
+            let spread = rng.gen_range(0.0..=1.0);
+            let waveform = get_rand_waveform(rng)?;
+
+            chorus(wav, voices, delay_ms, depth_ms, rate_hz, spread, waveform)
+        },
+        "phaser" => {
+            let stages = [2, 4, 6, 8, 12].choose(rng).cloned().unwrap();
+            let rate_hz = rng.gen_range(0.1..=4.0);
+            let depth = rng.gen_range(0.4..=1.0);
+            let feedback = rng.gen_range(-0.7..=0.7);
+            let waveform = get_rand_waveform(rng)?;
+
+            phaser(wav, stages, rate_hz, depth, feedback, waveform)
+        },
+        "delay" => {
+            let bpm = rng.gen_range(80.0..=160.0);
+            let note = ["1/4", "1/8", "1/8d", "1/16", "1/4t", "1/8t"].choose(rng).unwrap();
+            let time_ms = note_to_seconds(note, bpm)? * 1000.0;
+            let feedback = rng.gen_range(0.2..=0.6);
+            let ping_pong = rng.gen_bool(0.5);
+            let lowcut = rng.gen_range(100.0..=400.0);
+            let highcut = rng.gen_range(2000.0..=8000.0);
+
+            delay(wav, time_ms, feedback, ping_pong, Some(lowcut), Some(highcut), rng.gen_range(0.2..=0.5))
+        },
+        "tremolo" => {
+            let bpm = rng.gen_range(80.0..=160.0);
+            let note = ["1/4", "1/8", "1/8d", "1/16", "1/8t", "1/16t"].choose(rng).unwrap();
+            let rate_hz = 1.0 / note_to_seconds(note, bpm)?;
+            let depth = rng.gen_range(0.3..=1.0);
+            let waveform = get_rand_waveform(rng)?;
+
+            tremolo(wav, rate_hz, depth, waveform)
+        },
+        "autopan" => {
+            let rate_hz = rng.gen_range(0.1..=4.0);
+            let width = rng.gen_range(0.4..=1.0);
+            let waveform = get_rand_waveform(rng)?;
+
+            autopan(wav, rate_hz, width, waveform)
+        },
+        "ringmod" => {
+            let carrier_hz = rng.gen_range(30.0..=1200.0);
+            let waveform = get_rand_waveform(rng)?;
+            let mix = rng.gen_range(0.3..=1.0);
+
+            ringmod(wav, carrier_hz, waveform, mix)
+        },
+        "tape" => {
+            let settings = TapeSettings {
+                wow_depth_ms: rng.gen_range(0.5..=4.0),
+                wow_rate_hz: rng.gen_range(0.2..=1.5),
+                flutter_depth_ms: rng.gen_range(0.02..=0.3),
+                flutter_rate_hz: rng.gen_range(5.0..=12.0),
+                drive_db: rng.gen_range(0.0..=12.0),
+                head_bump_db: rng.gen_range(0.0..=5.0),
+                rolloff_hz: rng.gen_range(6000.0..=15000.0),
+                hiss_db: rng.gen_range(-70.0..=-45.0),
+            };
+
+            tape(wav, &settings, rng)
+        },
+        "vinyl" => {
+            let settings = VinylSettings {
+                crackle_density: rng.gen_range(10.0..=150.0),
+                pop_rate: rng.gen_range(0.0..=2.0),
+                rumble_db: rng.gen_range(-60.0..=-35.0),
+                lowcut_hz: rng.gen_range(30.0..=120.0),
+                highcut_hz: rng.gen_range(5000.0..=12000.0),
+            };
+
+            vinyl(wav, &settings, rng)
+        },
+        "tapestop" => {
+            let duration = ["1/8", "1/4", "1/3", "1/2"].choose(rng).unwrap();
+            let curve = rng.gen_range(0.5..=2.5);
+
+            tapestop(wav, duration, curve, false)
+        },
+        "stutter" => {
+            // Note divisions of the detected tempo, or fractions of the file when it is unclear
+            let tempo = bpm(wav, 60.0, 200.0).ok().filter(|t| t.confidence >= MIN_CONFIDENCE).map(|t| t.bpm);
+            let settings = StutterSettings {
+                grid: ["1/8", "1/16", "1/32"].choose(rng).unwrap().to_string(),
+                bpm: tempo,
+                probability: rng.gen_range(0.1..=0.5),
+                repeats: rng.gen_range(2..=8),
+                decay: rng.gen_range(0.6..=1.0),
+                pitch_drift: rng.gen_range(-2.0..=2.0),
+                gate: rng.gen_range(0.4..=1.0),
+            };
 
-            flanger(wav, delay_ms, depth_ms, rate_hz, feedback)
+            stutter(wav, &settings, rng)
+        },
+        "freeze" => {
+            let at = ["1/8", "1/4", "1/3", "1/2", "2/3"].choose(rng).unwrap();
+            spectral_freeze(wav, at, &StftSettings::default())
+        },
+        "blur" => spectral_blur(wav, rng.gen_range(0.7..0.97), &StftSettings::default()),
+        "specgate" => spectral_gate(wav, rng.gen_range(-60.0..=-30.0), &StftSettings::default()),
+        "fshift" => frequency_shift(wav, rng.gen_range(-400.0..=400.0), &StftSettings::default()),
+        "scramble" => {
+            let bands = rng.gen_range(4..=32);
+            spectral_scramble(wav, bands, rng, &StftSettings::default())
+        },
+        "gate" => {
+            let settings = GateSettings {
+                threshold_db: rng.gen_range(-45.0..=-20.0),
+                hysteresis_db: rng.gen_range(2.0..=6.0),
+                attack_ms: rng.gen_range(0.5..=5.0),
+                hold_ms: rng.gen_range(10.0..=80.0),
+                release_ms: rng.gen_range(30.0..=200.0),
+                range_db: rng.gen_range(20.0..=80.0),
+                stereo_link: true,
+            };
+
+            gate(wav, None, &settings)
+        },
+        "transient" => {
+            let settings = TransientSettings {
+                attack_db: rng.gen_range(-6.0..=12.0),
+                sustain_db: rng.gen_range(-12.0..=6.0),
+                sensitivity: rng.gen_range(0.5..=2.0),
+                output_db: 0.0,
+                stereo_link: rng.gen_bool(0.7),
+            };
+
+            transient(wav, &settings)
         },
         "faster" => speed(wav, rng.gen_range(1.1 ..= 1.9)),
         "slower" => speed(wav, rng.gen_range(0.5 ..= 0.9)),
@@ -48,7 +210,7 @@ pub fn apply_rand_fx_with_rng(wav:&[u8], rng: &mut StdRng) -> Result<Vec<u8>,Str
 }
 
 pub fn get_fx_list() -> Vec<String>{
-    ["bitcrush","flanger", "highpass","lowpass", "reverb","reverse"]
+    ["autopan","bitcrush","blur","chorus","delay","flanger","freeze","fshift","gate", "highpass","lowpass","phaser", "restretch", "reverb","reverse","ringmod","scramble","specgate","stutter","tape","tapestop","transient","tremolo","vinyl"]
         .into_iter()
         .map(String::from)
         .collect()
@@ -59,3 +221,7 @@ pub fn get_rand_fx(rng: &mut StdRng) -> String {
     println!("Resolved random fx: {}", fx);
     fx
 }
+
+fn get_rand_waveform(rng: &mut StdRng) -> Result<Waveform, String> {
+    get_waveform_list().choose(rng).unwrap().parse()
+}
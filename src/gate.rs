@@ -0,0 +1,224 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::{get_samples, wrap_samples};
+use crate::dynamics::{amplitude_to_db, db_to_amplitude, time_coefficient, EnvelopeFollower};
+
+// Time constants of the level detector (kept short so the gate reacts to transients)
+const DETECTOR_ATTACK_MS: f32 = 0.5;
+const DETECTOR_RELEASE_MS: f32 = 20.0;
+
+#[derive(Clone, Debug)]
+pub struct GateSettings {
+    pub threshold_db: f32,
+    // The gate closes only once the level falls this far below the threshold
+    pub hysteresis_db: f32,
+    pub attack_ms: f32,
+    pub hold_ms: f32,
+    pub release_ms: f32,
+    // Attenuation when closed: large values gate, small values expand downwards
+    pub range_db: f32,
+    pub stereo_link: bool,
+}
+
+impl Default for GateSettings {
+    fn default() -> Self {
+        GateSettings {
+            threshold_db: -40.0,
+            hysteresis_db: 4.0,
+            attack_ms: 1.0,
+            hold_ms: 50.0,
+            release_ms: 100.0,
+            range_db: 80.0,
+            stereo_link: true,
+        }
+    }
+}
+
+struct GateState {
+    detector: EnvelopeFollower,
+    open: bool,
+    hold_remaining: usize,
+    gain: f32,
+}
+
+impl GateState {
+    fn new(sample_rate: f32) -> Self {
+        GateState {
+            detector: EnvelopeFollower::new(DETECTOR_ATTACK_MS, DETECTOR_RELEASE_MS, sample_rate),
+            open: false,
+            hold_remaining: 0,
+            gain: 0.0,
+        }
+    }
+}
+
+// Gates `input_wav`, optionally keyed by another WAV instead of itself
+pub fn gate(input_wav: &[u8], key_wav: Option<&[u8]>, settings: &GateSettings) -> Result<Vec<u8>, String> {
+    if settings.hysteresis_db < 0.0 {
+        return Err("Hysteresis cannot be negative.".to_string());
+    }
+
+    let (mut samples, spec) = get_samples(input_wav)?;
+    let sample_rate = spec.sample_rate as f32;
+    let channels = spec.channels as usize;
+
+    let key = match key_wav {
+        Some(wav) => {
+            let (key_samples, key_spec) = get_samples(wav)?;
+            if key_spec.sample_rate != spec.sample_rate {
+                return Err("Sample rates do not match.".to_string());
+            }
+            Some((key_samples, key_spec.channels as usize))
+        }
+        None => None,
+    };
+
+    let closed_gain = db_to_amplitude(-settings.range_db.abs());
+    let hold_samples = (settings.hold_ms / 1000.0 * sample_rate) as usize;
+    let attack = time_coefficient(settings.attack_ms, sample_rate);
+    let release = time_coefficient(settings.release_ms, sample_rate);
+
+    let paths = if settings.stereo_link { 1 } else { channels };
+    let mut states: Vec<GateState> = (0..paths).map(|_| GateState::new(sample_rate)).collect();
+    for state in states.iter_mut() {
+        state.gain = closed_gain;
+    }
+
+    for (i, frame) in samples.chunks_mut(channels).enumerate() {
+        for (path, state) in states.iter_mut().enumerate() {
+            let level = match &key {
+                Some((key_samples, key_channels)) => {
+                    let key_frame = key_samples.get(i * key_channels..(i + 1) * key_channels).unwrap_or(&[]);
+                    if settings.stereo_link {
+                        key_frame.iter().fold(0.0f32, |acc, s| acc.max(s.abs()))
+                    } else {
+                        key_frame.get(path % key_channels).map(|s| s.abs()).unwrap_or(0.0)
+                    }
+                }
+                None if settings.stereo_link => frame.iter().fold(0.0f32, |acc, s| acc.max(s.abs())),
+                None => frame[path].abs(),
+            };
+
+            let level_db = amplitude_to_db(state.detector.process(level));
+
+            if level_db > settings.threshold_db {
+                state.open = true;
+                state.hold_remaining = hold_samples;
+            } else if level_db < settings.threshold_db - settings.hysteresis_db {
+                if state.hold_remaining > 0 {
+                    state.hold_remaining -= 1;
+                } else {
+                    state.open = false;
+                }
+            }
+
+            let target = if state.open { 1.0 } else { closed_gain };
+            let coefficient = if target > state.gain { attack } else { release };
+            state.gain = coefficient * state.gain + (1.0 - coefficient) * target;
+        }
+
+        for (ch, sample) in frame.iter_mut().enumerate() {
+            *sample *= states[if settings.stereo_link { 0 } else { ch }].gain;
+        }
+    }
+
+    wrap_samples(samples, spec)
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn gate_js(
+    input_wav: &[u8],
+    key_wav: Option<Vec<u8>>,
+    threshold_db: f32,
+    hysteresis_db: f32,
+    attack_ms: f32,
+    hold_ms: f32,
+    release_ms: f32,
+    range_db: f32,
+    stereo_link: bool,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let settings = GateSettings { threshold_db, hysteresis_db, attack_ms, hold_ms, release_ms, range_db, stereo_link };
+    match gate(input_wav, key_wav.as_deref(), &settings) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Applies a noise gate / downward expander to a WAV file", long_about = None)]
+pub struct GateArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Optional key WAV file that opens and closes the gate
+    #[arg(long)]
+    pub key: Option<String>,
+
+    /// Threshold in dBFS
+    #[arg(long, default_value_t = -40.0, allow_hyphen_values = true)]
+    pub threshold: f32,
+
+    /// Hysteresis in dB below the threshold before closing
+    #[arg(long, default_value_t = 4.0)]
+    pub hysteresis: f32,
+
+    /// Attack (opening) time in milliseconds
+    #[arg(long, default_value_t = 1.0)]
+    pub attack: f32,
+
+    /// Hold time in milliseconds
+    #[arg(long, default_value_t = 50.0)]
+    pub hold: f32,
+
+    /// Release (closing) time in milliseconds
+    #[arg(long, default_value_t = 100.0)]
+    pub release: f32,
+
+    /// Attenuation when closed in dB (small values expand instead of gating)
+    #[arg(long, default_value_t = 80.0)]
+    pub range: f32,
+
+    /// Process channels independently instead of stereo-linked
+    #[arg(long)]
+    pub unlinked: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+    use hound::{WavSpec, SampleFormat};
+
+    #[test]
+    fn test_gate_silences_quiet_parts() {
+        let spec = WavSpec { channels: 1, sample_rate: 1000, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        // Loud burst followed by low-level noise
+        let mut samples: Vec<f32> = (0..200).map(|i| if i % 2 == 0 { 0.8 } else { -0.8 }).collect();
+        samples.extend((0..800).map(|i| if i % 2 == 0 { 0.001 } else { -0.001 }));
+        let input_wav = wrap_samples(samples, spec).unwrap();
+
+        let settings = GateSettings { hold_ms: 10.0, release_ms: 10.0, ..Default::default() };
+        let output_wav = gate(&input_wav, None, &settings).expect("gate function failed");
+        let (output, _) = get_samples(&output_wav).unwrap();
+
+        assert!(output[150].abs() > 0.7, "Loud parts should pass through the gate");
+        assert!(output[900].abs() < 1e-5, "Quiet parts should be gated");
+    }
+
+    #[test]
+    fn test_gate_with_key() {
+        let input_wav = get_dummy();
+        let key_wav = get_dummy();
+
+        let output_wav = gate(&input_wav, Some(&key_wav), &GateSettings::default()).expect("gate function failed");
+
+        assert_eq!(len(&input_wav).unwrap(), len(&output_wav).unwrap(), "Gating should not change the duration");
+    }
+}
@@ -0,0 +1,208 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::utils::{get_samples, wrap_samples};
+use crate::imaging::{pan_gains, PanLaw};
+use crate::window::Window;
+
+#[derive(Clone, Debug)]
+pub struct GranularSettings {
+    pub grain_ms: f32,
+    // Average number of grains started per second
+    pub density: f32,
+    // Where grains are read from, 0.0 (start) to 1.0 (end) of each source
+    pub position: f32,
+    // Random spread around the position, as a fraction of the source length
+    pub jitter: f32,
+    // Maximum random pitch change of a grain in semitones (either way)
+    pub pitch_jitter: f32,
+    pub window: Window,
+    // Random placement in the stereo field (0.0 centred, 1.0 anywhere)
+    pub spread: f32,
+    pub duration_s: f32,
+}
+
+impl Default for GranularSettings {
+    fn default() -> Self {
+        GranularSettings {
+            grain_ms: 80.0,
+            density: 30.0,
+            position: 0.5,
+            jitter: 0.2,
+            pitch_jitter: 0.0,
+            window: Window::Hann,
+            spread: 0.5,
+            duration_s: 4.0,
+        }
+    }
+}
+
+// Scatters windowed grains from `sources` into a new stereo texture
+pub fn granular(sources: &[&[u8]], settings: &GranularSettings, rng: &mut StdRng) -> Result<Vec<u8>, String> {
+    if sources.is_empty() {
+        return Err("Granular needs at least one source.".to_string());
+    }
+    if settings.grain_ms <= 0.0 || settings.density <= 0.0 || settings.duration_s <= 0.0 {
+        return Err("Grain size, density and duration must be positive.".to_string());
+    }
+
+    // Sources are summed to mono, grains get their own place in the stereo field
+    let mut mono_sources = Vec::with_capacity(sources.len());
+    let mut spec = None;
+    for wav in sources {
+        let (samples, source_spec) = get_samples(wav)?;
+        if spec.is_some_and(|s: hound::WavSpec| s.sample_rate != source_spec.sample_rate) {
+            return Err("Sample rates do not match.".to_string());
+        }
+        let channels = source_spec.channels as usize;
+        mono_sources.push(samples.chunks(channels).map(|f| f.iter().sum::<f32>() / channels as f32).collect::<Vec<f32>>());
+        spec.get_or_insert(source_spec);
+    }
+    let mut spec = spec.unwrap();
+    spec.channels = 2;
+
+    let sample_rate = spec.sample_rate as f32;
+    let grain_frames = ((settings.grain_ms / 1000.0 * sample_rate) as usize).max(2);
+    let output_frames = (settings.duration_s * sample_rate) as usize;
+    let grain_count = (settings.density * settings.duration_s).round() as usize;
+
+    // Overlapping grains add up, so the level is scaled by the expected overlap
+    let overlap = settings.density * settings.grain_ms / 1000.0;
+    let gain = 1.0 / overlap.max(1.0).sqrt();
+    let window = settings.window.coefficients(grain_frames);
+
+    let mut output = vec![0.0f32; output_frames * 2];
+
+    for _ in 0..grain_count {
+        let source = &mono_sources[rng.gen_range(0..mono_sources.len())];
+        let onset = rng.gen_range(0..output_frames.max(1));
+        let centre = settings.position + settings.jitter * rng.gen_range(-0.5..=0.5);
+        let start = (centre.clamp(0.0, 1.0) * source.len() as f32) as usize;
+        let rate = 2.0f32.powf(rng.gen_range(-1.0..=1.0) * settings.pitch_jitter / 12.0);
+        let (left_gain, right_gain) = pan_gains(rng.gen_range(-1.0..=1.0) * settings.spread, PanLaw::EqualPower);
+
+        for (i, w) in window.iter().enumerate() {
+            let frame = onset + i;
+            if frame >= output_frames {
+                break;
+            }
+            let read = start as f32 + i as f32 * rate;
+            let index = read as usize;
+            let (Some(&a), Some(&b)) = (source.get(index), source.get(index + 1)) else {
+                break;
+            };
+            let sample = (a + (b - a) * (read - index as f32)) * w * gain;
+            output[frame * 2] += sample * left_gain;
+            output[frame * 2 + 1] += sample * right_gain;
+        }
+    }
+
+    wrap_samples(output, spec)
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn granular_js(
+    wav1: Vec<u8>,
+    wav2: Option<Vec<u8>>,
+    grain_ms: f32,
+    density: f32,
+    position: f32,
+    jitter: f32,
+    pitch_jitter: f32,
+    window: &str,
+    spread: f32,
+    duration_s: f32,
+    seed: u64,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let mut sources: Vec<&[u8]> = vec![wav1.as_slice()];
+    if let Some(wav) = wav2.as_deref() {
+        sources.push(wav);
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let result = window.parse::<Window>().and_then(|window| {
+        let settings = GranularSettings { grain_ms, density, position, jitter, pitch_jitter, window, spread, duration_s };
+        granular(&sources, &settings, &mut rng)
+    });
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Builds a granular texture from one or more WAV files", long_about = None)]
+pub struct GranularArgs {
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Source WAV files
+    #[arg(required = true)]
+    pub inputs: Vec<String>,
+
+    /// Grain size in milliseconds
+    #[arg(long, default_value_t = 80.0)]
+    pub grain: f32,
+
+    /// Grains per second
+    #[arg(long, default_value_t = 30.0)]
+    pub density: f32,
+
+    /// Read position in the sources (0.0 to 1.0)
+    #[arg(long, default_value_t = 0.5)]
+    pub position: f32,
+
+    /// Random spread around the read position (0.0 to 1.0)
+    #[arg(long, default_value_t = 0.2)]
+    pub jitter: f32,
+
+    /// Maximum random pitch change per grain in semitones
+    #[arg(long, default_value_t = 0.0)]
+    pub pitch: f32,
+
+    /// Grain window (hann, triangle, gaussian, rectangle)
+    #[arg(long, default_value = "hann")]
+    pub window: String,
+
+    /// Stereo spread (0.0 to 1.0)
+    #[arg(long, default_value_t = 0.5)]
+    pub spread: f32,
+
+    /// Output duration in seconds
+    #[arg(long, default_value_t = 4.0)]
+    pub duration: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+
+    #[test]
+    fn test_granular_texture() {
+        let source = get_dummy();
+        let settings = GranularSettings { duration_s: 2.0, pitch_jitter: 5.0, window: Window::Gaussian, ..Default::default() };
+
+        let output_wav = granular(&[&source, &source], &settings, &mut StdRng::seed_from_u64(0))
+            .expect("granular function failed");
+
+        let (samples, spec) = get_samples(&output_wav).unwrap();
+        assert_eq!(spec.channels, 2, "Granular output should be stereo");
+        assert!((len(&output_wav).unwrap() - 2.0).abs() < 1e-3, "Output should last the requested duration");
+        assert!(samples.iter().any(|s| s.abs() > 1e-3), "Grains should be audible");
+    }
+
+    #[test]
+    fn test_granular_is_reproducible() {
+        let source = get_dummy();
+        let settings = GranularSettings { duration_s: 1.0, ..Default::default() };
+
+        let a = granular(&[&source], &settings, &mut StdRng::seed_from_u64(9)).unwrap();
+        let b = granular(&[&source], &settings, &mut StdRng::seed_from_u64(9)).unwrap();
+
+        assert_eq!(a, b, "The same seed should give the same result");
+    }
+}
@@ -0,0 +1,419 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use std::f32::consts::FRAC_PI_4;
+use std::str::FromStr;
+use crate::utils::{get_samples, wrap_samples};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PanLaw {
+    Linear,     // -6 dB in the centre
+    EqualPower, // -3 dB in the centre, constant loudness across the field
+    Compromise, // -4.5 dB in the centre
+}
+
+impl FromStr for PanLaw {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(PanLaw::Linear),
+            "equal" => Ok(PanLaw::EqualPower),
+            "compromise" => Ok(PanLaw::Compromise),
+            _ => Err(format!("Pan law not recognized: {}", s)),
+        }
+    }
+}
+
+// Left and right gains for a position from -1.0 (hard left) to 1.0 (hard right)
+pub fn pan_gains(position: f32, law: PanLaw) -> (f32, f32) {
+    let position = position.clamp(-1.0, 1.0);
+    let linear = ((1.0 - position) / 2.0, (1.0 + position) / 2.0);
+    let angle = (position + 1.0) * FRAC_PI_4;
+    let equal_power = (angle.cos(), angle.sin());
+
+    match law {
+        PanLaw::Linear => linear,
+        PanLaw::EqualPower => equal_power,
+        PanLaw::Compromise => ((linear.0 * equal_power.0).sqrt(), (linear.1 * equal_power.1).sqrt()),
+    }
+}
+
+pub fn mid_side(left: f32, right: f32) -> (f32, f32) {
+    ((left + right) / 2.0, (left - right) / 2.0)
+}
+
+pub fn left_right(mid: f32, side: f32) -> (f32, f32) {
+    (mid + side, mid - side)
+}
+
+// Interleaved stereo from mono or stereo samples (mono is duplicated)
+pub fn to_stereo(samples: &[f32], channels: usize) -> Result<Vec<f32>, String> {
+    match channels {
+        1 => Ok(samples.iter().flat_map(|&s| [s, s]).collect()),
+        2 => Ok(samples.to_vec()),
+        _ => Err("Only mono or stereo input is supported.".to_string()),
+    }
+}
+
+// Pearson correlation between the channels of interleaved stereo samples:
+// 1.0 is mono, 0.0 unrelated, negative values cancel when summed to mono
+pub fn correlation(stereo: &[f32]) -> f32 {
+    let (mut lr, mut ll, mut rr) = (0.0f64, 0.0f64, 0.0f64);
+    for frame in stereo.chunks_exact(2) {
+        let (l, r) = (frame[0] as f64, frame[1] as f64);
+        lr += l * r;
+        ll += l * l;
+        rr += r * r;
+    }
+    if ll == 0.0 || rr == 0.0 {
+        return 1.0;
+    }
+    (lr / (ll * rr).sqrt()) as f32
+}
+
+// Reads a WAV as interleaved stereo, along with a stereo spec to write it back
+fn read_stereo(input_wav: &[u8]) -> Result<(Vec<f32>, hound::WavSpec), String> {
+    let (samples, mut spec) = get_samples(input_wav)?;
+    let stereo = to_stereo(&samples, spec.channels as usize)?;
+    spec.channels = 2;
+    Ok((stereo, spec))
+}
+
+// Places the input (summed to mono) at `position` in the stereo field
+pub fn pan(input_wav: &[u8], position: f32, law: PanLaw) -> Result<Vec<u8>, String> {
+    if !(-1.0..=1.0).contains(&position) {
+        return Err("Pan position must be between -1.0 and 1.0.".to_string());
+    }
+
+    let (stereo, spec) = read_stereo(input_wav)?;
+    let (left_gain, right_gain) = pan_gains(position, law);
+
+    let mut output = Vec::with_capacity(stereo.len());
+    for frame in stereo.chunks_exact(2) {
+        let mono = (frame[0] + frame[1]) / 2.0;
+        output.push(mono * left_gain);
+        output.push(mono * right_gain);
+    }
+
+    wrap_samples(output, spec)
+}
+
+// Turns down the opposite side, keeping the stereo image intact
+pub fn balance(input_wav: &[u8], balance: f32) -> Result<Vec<u8>, String> {
+    if !(-1.0..=1.0).contains(&balance) {
+        return Err("Balance must be between -1.0 and 1.0.".to_string());
+    }
+
+    let (mut stereo, spec) = read_stereo(input_wav)?;
+    let left_gain = (1.0 - balance).min(1.0);
+    let right_gain = (1.0 + balance).min(1.0);
+
+    for frame in stereo.chunks_exact_mut(2) {
+        frame[0] *= left_gain;
+        frame[1] *= right_gain;
+    }
+
+    wrap_samples(stereo, spec)
+}
+
+// Scales the side signal: 0.0 collapses to mono, 1.0 leaves the image unchanged,
+// values above 1.0 widen it
+pub fn width(input_wav: &[u8], width: f32) -> Result<Vec<u8>, String> {
+    if width < 0.0 {
+        return Err("Width cannot be negative.".to_string());
+    }
+
+    let (mut stereo, spec) = read_stereo(input_wav)?;
+
+    for frame in stereo.chunks_exact_mut(2) {
+        let (mid, side) = mid_side(frame[0], frame[1]);
+        let (left, right) = left_right(mid, side * width);
+        frame[0] = left;
+        frame[1] = right;
+    }
+
+    wrap_samples(stereo, spec)
+}
+
+// Stereo to mid/side: the left channel carries mid, the right carries side
+pub fn ms_encode(input_wav: &[u8]) -> Result<Vec<u8>, String> {
+    let (mut stereo, spec) = read_stereo(input_wav)?;
+
+    for frame in stereo.chunks_exact_mut(2) {
+        let (mid, side) = mid_side(frame[0], frame[1]);
+        frame[0] = mid;
+        frame[1] = side;
+    }
+
+    wrap_samples(stereo, spec)
+}
+
+// Inverse of ms_encode
+pub fn ms_decode(input_wav: &[u8]) -> Result<Vec<u8>, String> {
+    let (samples, spec) = get_samples(input_wav)?;
+    if spec.channels != 2 {
+        return Err("Mid/side decoding needs a stereo (M/S encoded) input.".to_string());
+    }
+
+    let mut output = samples;
+    for frame in output.chunks_exact_mut(2) {
+        let (left, right) = left_right(frame[0], frame[1]);
+        frame[0] = left;
+        frame[1] = right;
+    }
+
+    wrap_samples(output, spec)
+}
+
+// Widens by delaying one channel a few milliseconds (precedence effect).
+// Positive delays push the right channel back, negative ones the left.
+pub fn haas(input_wav: &[u8], delay_ms: f32) -> Result<Vec<u8>, String> {
+    if delay_ms.abs() > 50.0 {
+        return Err("Haas delay must be within 50 ms, beyond that it is heard as an echo.".to_string());
+    }
+
+    let (stereo, spec) = read_stereo(input_wav)?;
+    let delay_frames = (delay_ms.abs() / 1000.0 * spec.sample_rate as f32).round() as usize;
+    let delayed_channel = if delay_ms >= 0.0 { 1 } else { 0 };
+    let frames = stereo.len() / 2;
+
+    let mut output = stereo.clone();
+    for i in 0..frames {
+        output[i * 2 + delayed_channel] = if i >= delay_frames {
+            stereo[(i - delay_frames) * 2 + delayed_channel]
+        } else {
+            0.0
+        };
+    }
+
+    wrap_samples(output, spec)
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct MonoCheck {
+    pub correlation: f32,
+    // Level change in dB when the channels are summed to mono
+    pub mono_loss_db: f32,
+}
+
+pub fn mono_check(input_wav: &[u8]) -> Result<MonoCheck, String> {
+    let (stereo, _) = read_stereo(input_wav)?;
+
+    let mut stereo_energy = 0.0f64;
+    let mut mono_energy = 0.0f64;
+    for frame in stereo.chunks_exact(2) {
+        let (mid, _) = mid_side(frame[0], frame[1]);
+        stereo_energy += ((frame[0] * frame[0] + frame[1] * frame[1]) / 2.0) as f64;
+        mono_energy += (mid * mid) as f64;
+    }
+
+    let mono_loss_db = if stereo_energy > 0.0 {
+        (10.0 * (mono_energy.max(1e-20) / stereo_energy).log10()) as f32
+    } else {
+        0.0
+    };
+
+    Ok(MonoCheck { correlation: correlation(&stereo), mono_loss_db })
+}
+
+#[wasm_bindgen]
+pub fn pan_js(input_wav: &[u8], position: f32, law: &str) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = law.parse::<PanLaw>().and_then(|law| pan(input_wav, position, law));
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[wasm_bindgen]
+pub fn balance_js(input_wav: &[u8], balance_value: f32) -> Result<js_sys::Uint8Array, JsValue> {
+    match balance(input_wav, balance_value) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[wasm_bindgen]
+pub fn width_js(input_wav: &[u8], width_value: f32) -> Result<js_sys::Uint8Array, JsValue> {
+    match width(input_wav, width_value) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[wasm_bindgen]
+pub fn ms_encode_js(input_wav: &[u8]) -> Result<js_sys::Uint8Array, JsValue> {
+    match ms_encode(input_wav) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[wasm_bindgen]
+pub fn ms_decode_js(input_wav: &[u8]) -> Result<js_sys::Uint8Array, JsValue> {
+    match ms_decode(input_wav) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[wasm_bindgen]
+pub fn haas_js(input_wav: &[u8], delay_ms: f32) -> Result<js_sys::Uint8Array, JsValue> {
+    match haas(input_wav, delay_ms) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[wasm_bindgen]
+pub fn mono_check_js(input_wav: &[u8]) -> Result<MonoCheck, JsValue> {
+    mono_check(input_wav).map_err(|e| JsValue::from_str(&e))
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Pans a WAV file (summed to mono) in the stereo field", long_about = None)]
+pub struct PanArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Position from -1.0 (left) to 1.0 (right)
+    #[arg(allow_hyphen_values = true)]
+    pub position: f32,
+
+    /// Pan law (linear, equal, compromise)
+    #[arg(long, default_value = "equal")]
+    pub law: String,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Adjusts the left/right balance of a stereo WAV file", long_about = None)]
+pub struct BalanceArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Balance from -1.0 (left only) to 1.0 (right only)
+    #[arg(allow_hyphen_values = true)]
+    pub balance: f32,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Narrows or widens the stereo image of a WAV file", long_about = None)]
+pub struct WidthArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Width (0.0 mono, 1.0 unchanged, 2.0 twice as wide)
+    #[arg()]
+    pub width: f32,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Checks how well a stereo WAV file survives summing to mono", long_about = None)]
+pub struct MonoCheckArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Encodes a stereo WAV file to mid (left) / side (right)", long_about = None)]
+pub struct MsEncodeArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Decodes a mid/side WAV file back to left/right", long_about = None)]
+pub struct MsDecodeArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Widens a WAV file by delaying one channel (Haas effect)", long_about = None)]
+pub struct HaasArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Delay in milliseconds (positive delays the right channel, negative the left)
+    #[arg(default_value_t = 15.0, allow_hyphen_values = true)]
+    pub delay: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::get_dummy;
+
+    #[test]
+    fn test_pan_laws() {
+        let centre = |law| pan_gains(0.0, law).0;
+        assert!((centre(PanLaw::Linear) - 0.5).abs() < 1e-6, "Linear law is -6 dB in the centre");
+        assert!((centre(PanLaw::EqualPower) - 0.5f32.sqrt()).abs() < 1e-6, "Equal power law is -3 dB in the centre");
+        assert!((20.0 * centre(PanLaw::Compromise).log10() + 4.5).abs() < 0.1, "Compromise law is -4.5 dB in the centre");
+
+        let (left, right) = pan_gains(1.0, PanLaw::EqualPower);
+        assert!(left.abs() < 1e-6 && (right - 1.0).abs() < 1e-6, "Hard right should mute the left channel");
+    }
+
+    #[test]
+    fn test_ms_roundtrip_and_width() {
+        let input_wav = get_dummy();
+        let (input_samples, _) = get_samples(&input_wav).unwrap();
+
+        let decoded_wav = ms_decode(&ms_encode(&input_wav).unwrap()).unwrap();
+        let (decoded_samples, _) = get_samples(&decoded_wav).unwrap();
+        for (a, b) in input_samples.iter().zip(decoded_samples.iter()) {
+            assert!((a - b).abs() < 1e-5, "M/S encode then decode should give back the input");
+        }
+
+        let mono_wav = width(&input_wav, 0.0).unwrap();
+        let check = mono_check(&mono_wav).unwrap();
+        assert!((check.correlation - 1.0).abs() < 1e-4, "Zero width should be fully correlated");
+        assert!(check.mono_loss_db.abs() < 1e-3, "A mono signal loses nothing when summed");
+    }
+
+    #[test]
+    fn test_haas_delays_one_channel() {
+        let spec = hound::WavSpec { channels: 1, sample_rate: 1000, bits_per_sample: 32, sample_format: hound::SampleFormat::Float };
+        let input_wav = wrap_samples((0..100).map(|i| i as f32 / 100.0).collect(), spec).unwrap();
+
+        let output_wav = haas(&input_wav, 10.0).unwrap();
+        let (output, output_spec) = get_samples(&output_wav).unwrap();
+
+        assert_eq!(output_spec.channels, 2);
+        assert_eq!(output[50 * 2], 0.5, "The left channel should be untouched");
+        assert_eq!(output[50 * 2 + 1], 0.4, "The right channel should be 10 samples late");
+    }
+}
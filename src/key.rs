@@ -0,0 +1,238 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::get_samples;
+use crate::stft::{stft, StftSettings};
+use crate::window::Window;
+use crate::pitch::NOTE_NAMES;
+
+// Long frames for a fine frequency resolution (about 5.4 Hz at 44.1 kHz)
+const CHROMA_FFT_SIZE: usize = 8192;
+const CHROMA_HOP: usize = 4096;
+const HIGHEST_HZ: f32 = 2100.0;
+// Spread of the chroma (standard deviation over mean) from which it counts as
+// fully tonal. Noise and most drum loops stay far below it.
+const TONAL_CONTRAST: f32 = 0.5;
+// Below this confidence a file has no clear key
+pub const MIN_CONFIDENCE: f32 = 0.4;
+
+// Krumhansl-Kessler key profiles, from C
+const MAJOR_PROFILE: [f32; 12] = [6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88];
+const MINOR_PROFILE: [f32; 12] = [6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17];
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Key {
+    // Pitch class of the tonic, 0 = C
+    pub tonic: u8,
+    pub minor: bool,
+    // Correlation of the chroma with the key profile, scaled down when the
+    // chroma is nearly flat, 0..1
+    pub confidence: f32,
+}
+
+#[wasm_bindgen]
+impl Key {
+    pub fn name(&self) -> String {
+        format!("{} {}", NOTE_NAMES[self.tonic as usize], if self.minor { "minor" } else { "major" })
+    }
+
+    // Position on the Camelot wheel, e.g. "8B" for C major and "8A" for A minor
+    pub fn camelot(&self) -> String {
+        format!("{}{}", self.camelot_number(), if self.minor { "A" } else { "B" })
+    }
+
+    // Same key, relative major/minor, or one step round the circle of fifths
+    pub fn is_compatible(&self, other: &Key) -> bool {
+        let distance = (self.camelot_number() as i32 - other.camelot_number() as i32).rem_euclid(12);
+        if self.minor == other.minor {
+            distance <= 1 || distance == 11
+        } else {
+            distance == 0
+        }
+    }
+
+    fn camelot_number(&self) -> u8 {
+        // Minor keys sit with their relative major, three semitones up
+        let major_tonic = if self.minor { (self.tonic + 3) % 12 } else { self.tonic };
+        let fifths = (major_tonic as u32 * 7) % 12;
+        ((fifths + 7) % 12 + 1) as u8
+    }
+}
+
+// Energy per pitch class (C to B), summed over the whole file
+pub fn chroma(input_wav: &[u8]) -> Result<[f32; 12], String> {
+    let (samples, spec) = get_samples(input_wav)?;
+    let channels = spec.channels as usize;
+    let sample_rate = spec.sample_rate as f32;
+    let mono: Vec<f32> = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+
+    // Below this, neighbouring semitones are closer than one bin and would
+    // blur into each other (about 90 Hz at 44.1 kHz)
+    let bin_hz = sample_rate / CHROMA_FFT_SIZE as f32;
+    let lowest_hz = bin_hz / (2.0f32.powf(1.0 / 12.0) - 1.0);
+
+    let settings = StftSettings { fft_size: CHROMA_FFT_SIZE, hop: CHROMA_HOP, window: Window::Hann };
+    let pitch_classes: Vec<Option<usize>> = (0..settings.bins())
+        .map(|bin| {
+            let frequency = bin as f32 * sample_rate / CHROMA_FFT_SIZE as f32;
+            if (lowest_hz..=HIGHEST_HZ).contains(&frequency) {
+                let midi = (12.0 * (frequency / 440.0).log2() + 69.0).round() as i32;
+                Some(midi.rem_euclid(12) as usize)
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    // Pitch classes cover different numbers of bins, so each is averaged over
+    // its own bins and a flat spectrum gives a flat chroma
+    let mut bin_counts = [0usize; 12];
+    for pc in pitch_classes.iter().flatten() {
+        bin_counts[*pc] += 1;
+    }
+
+    let mut chroma = [0.0f32; 12];
+    for frame in stft(&mono, &settings)? {
+        for (bin, pitch_class) in frame.iter().zip(&pitch_classes) {
+            if let Some(pc) = pitch_class {
+                chroma[*pc] += bin.norm() / bin_counts[*pc] as f32;
+            }
+        }
+    }
+    Ok(chroma)
+}
+
+fn correlation(a: &[f32; 12], b: &[f32; 12]) -> f32 {
+    let mean_a = a.iter().sum::<f32>() / 12.0;
+    let mean_b = b.iter().sum::<f32>() / 12.0;
+    let (mut covariance, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        covariance += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a) * (x - mean_a);
+        var_b += (y - mean_b) * (y - mean_b);
+    }
+    if var_a == 0.0 || var_b == 0.0 { 0.0 } else { covariance / (var_a * var_b).sqrt() }
+}
+
+// Best-correlating of the 24 major and minor keys
+pub fn key(input_wav: &[u8]) -> Result<Key, String> {
+    let chroma = chroma(input_wav)?;
+    if chroma.iter().all(|&c| c == 0.0) {
+        return Err("Input has no pitched content to detect the key from.".to_string());
+    }
+
+    let mean = chroma.iter().sum::<f32>() / 12.0;
+    let deviation = (chroma.iter().map(|c| (c - mean) * (c - mean)).sum::<f32>() / 12.0).sqrt();
+    let tonality = (deviation / mean / TONAL_CONTRAST).min(1.0);
+
+    let mut best = Key { tonic: 0, minor: false, confidence: f32::NEG_INFINITY };
+    for tonic in 0..12 {
+        let rotated: [f32; 12] = std::array::from_fn(|pc| chroma[(pc + tonic) % 12]);
+        for (minor, profile) in [(false, &MAJOR_PROFILE), (true, &MINOR_PROFILE)] {
+            let confidence = correlation(&rotated, profile);
+            if confidence > best.confidence {
+                best = Key { tonic: tonic as u8, minor, confidence };
+            }
+        }
+    }
+    best.confidence = (best.confidence * tonality).max(0.0);
+    Ok(best)
+}
+
+pub fn key_to_json(key: &Key) -> String {
+    format!(
+        "{{\"key\":\"{}\",\"tonic\":\"{}\",\"mode\":\"{}\",\"camelot\":\"{}\",\"confidence\":{:.3}}}",
+        key.name(),
+        NOTE_NAMES[key.tonic as usize],
+        if key.minor { "minor" } else { "major" },
+        key.camelot(),
+        key.confidence
+    )
+}
+
+#[wasm_bindgen]
+pub fn key_js(input_wav: &[u8]) -> Result<Key, JsValue> {
+    key(input_wav).map_err(|e| JsValue::from_str(&e))
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Estimates the musical key of a WAV file", long_about = None)]
+pub struct KeyArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Print the key as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::wrap_samples;
+    use hound::{WavSpec, SampleFormat};
+    use std::f32::consts::PI;
+
+    // One second per chord, each chord given as MIDI notes
+    fn chords(progression: &[&[i32]]) -> Vec<u8> {
+        let spec = WavSpec { channels: 1, sample_rate: 44100, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        let samples = progression
+            .iter()
+            .flat_map(|notes| {
+                (0..44100).map(move |i| {
+                    notes
+                        .iter()
+                        .map(|&n| 0.2 * (2.0 * PI * 440.0 * 2.0f32.powf((n - 69) as f32 / 12.0) * i as f32 / 44100.0).sin())
+                        .sum::<f32>()
+                })
+            })
+            .collect();
+        wrap_samples(samples, spec).unwrap()
+    }
+
+    #[test]
+    fn test_key_of_progressions() {
+        // C - F - G - C
+        let c_major = key(&chords(&[&[60, 64, 67], &[65, 69, 72], &[67, 71, 74], &[60, 64, 67]])).unwrap();
+        assert_eq!(c_major.name(), "C major");
+        assert_eq!(c_major.camelot(), "8B");
+        assert!(c_major.confidence >= MIN_CONFIDENCE);
+
+        // Am - Dm - E - Am
+        let a_minor = key(&chords(&[&[57, 60, 64], &[62, 65, 69], &[64, 68, 71], &[57, 60, 64]])).unwrap();
+        assert_eq!(a_minor.name(), "A minor");
+        assert_eq!(a_minor.camelot(), "8A");
+    }
+
+    #[test]
+    fn test_noise_has_no_clear_key() {
+        let spec = WavSpec { channels: 1, sample_rate: 44100, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        // Deterministic white noise from a linear congruential generator
+        let mut state = 12345u32;
+        let noise = (0..88200)
+            .map(|_| {
+                state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+                (state >> 8) as f32 / (1 << 24) as f32 - 0.5
+            })
+            .collect();
+        let noise_key = key(&wrap_samples(noise, spec).unwrap()).unwrap();
+        assert!(noise_key.confidence < MIN_CONFIDENCE, "Noise confidence {}", noise_key.confidence);
+    }
+
+    #[test]
+    fn test_key_compatibility() {
+        let key = |tonic, minor| Key { tonic, minor, confidence: 1.0 };
+        let c_major = key(0, false);
+
+        assert!(c_major.is_compatible(&key(7, false)), "G major is a fifth up");
+        assert!(c_major.is_compatible(&key(5, false)), "F major is a fifth down");
+        assert!(c_major.is_compatible(&key(9, true)), "A minor is the relative minor");
+        assert!(!c_major.is_compatible(&key(2, false)), "D major is two fifths away");
+        assert!(!c_major.is_compatible(&key(4, true)), "E minor is not the relative minor");
+        assert!(key(11, false).is_compatible(&key(6, false)), "The wheel wraps around");
+    }
+}
@@ -0,0 +1,122 @@
+use std::f32::consts::PI;
+use std::str::FromStr;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Waveform {
+    Sine,
+    Triangle,
+    Square,
+    Saw,
+    Random, // Sample & hold: a new random level every cycle
+}
+
+impl FromStr for Waveform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sine" => Ok(Waveform::Sine),
+            "triangle" => Ok(Waveform::Triangle),
+            "square" => Ok(Waveform::Square),
+            "saw" => Ok(Waveform::Saw),
+            "random" => Ok(Waveform::Random),
+            _ => Err(format!("Waveform not recognized: {}", s)),
+        }
+    }
+}
+
+pub fn get_waveform_list() -> Vec<String> {
+    ["sine", "triangle", "square", "saw", "random"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+// Low frequency oscillator producing values in -1.0..=1.0.
+// The phase is kept in cycles (0.0..1.0) so it never loses precision.
+pub struct Lfo {
+    waveform: Waveform,
+    phase: f32,
+    increment: f32,
+    held: f32,
+    rng: StdRng,
+}
+
+impl Lfo {
+    pub fn new(waveform: Waveform, rate_hz: f32, sample_rate: f32) -> Self {
+        Lfo::with_phase(waveform, rate_hz, sample_rate, 0.0)
+    }
+
+    // Starts the oscillator at `phase` cycles (e.g. 0.25 for a quarter turn)
+    pub fn with_phase(waveform: Waveform, rate_hz: f32, sample_rate: f32, phase: f32) -> Self {
+        // The S&H sequence is fixed per phase so renders are repeatable
+        let mut rng = StdRng::seed_from_u64((phase.rem_euclid(1.0) * 1_000_000.0) as u64);
+        let held = rng.gen_range(-1.0..=1.0);
+        Lfo {
+            waveform,
+            phase: phase.rem_euclid(1.0),
+            increment: rate_hz / sample_rate,
+            held,
+            rng,
+        }
+    }
+
+    // Returns the current value and advances by one sample
+    pub fn next_value(&mut self) -> f32 {
+        let p = self.phase;
+        let value = match self.waveform {
+            Waveform::Sine => (2.0 * PI * p).sin(),
+            Waveform::Triangle => 1.0 - 4.0 * (p - 0.25 - (p - 0.25).round()).abs(),
+            Waveform::Square => if p < 0.5 { 1.0 } else { -1.0 },
+            Waveform::Saw => 2.0 * p - 1.0,
+            Waveform::Random => self.held,
+        };
+
+        self.phase += self.increment;
+        if self.phase >= 1.0 {
+            self.phase = self.phase.fract();
+            self.held = self.rng.gen_range(-1.0..=1.0);
+        }
+
+        value
+    }
+
+    // Same as next_value but mapped to 0.0..=1.0
+    pub fn next_unipolar(&mut self) -> f32 {
+        self.next_value() * 0.5 + 0.5
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waveforms_stay_in_range() {
+        for name in get_waveform_list() {
+            let waveform: Waveform = name.parse().unwrap();
+            let mut lfo = Lfo::new(waveform, 3.0, 100.0);
+            for _ in 0..500 {
+                let v = lfo.next_value();
+                assert!((-1.0..=1.0).contains(&v), "{} produced {} out of range", name, v);
+            }
+        }
+    }
+
+    #[test]
+    fn test_triangle_shape() {
+        let mut lfo = Lfo::new(Waveform::Triangle, 1.0, 4.0);
+        let values: Vec<f32> = (0..4).map(|_| lfo.next_value()).collect();
+        let expected = [0.0, 1.0, 0.0, -1.0];
+        for (v, e) in values.iter().zip(expected.iter()) {
+            assert!((v - e).abs() < 1e-5, "Triangle should go 0, 1, 0, -1 but got {:?}", values);
+        }
+    }
+
+    #[test]
+    fn test_unknown_waveform() {
+        assert!("wobble".parse::<Waveform>().is_err(), "Unknown waveforms should be rejected");
+    }
+}
@@ -27,6 +27,41 @@ pub mod mix;
 pub mod silence;
 pub mod fx;
 pub mod join;
+pub mod fft;
+pub mod convolve;
+pub mod filter;
+pub mod delay;
+pub mod lfo;
+pub mod chorus;
+pub mod phaser;
+pub mod dynamics;
+pub mod duck;
+pub mod gate;
+pub mod restretch;
+pub mod tremolo;
+pub mod autopan;
+pub mod ringmod;
+pub mod imaging;
+pub mod eq;
+pub mod tape;
+pub mod vinyl;
+pub mod tapestop;
+pub mod stutter;
+pub mod window;
+pub mod granular;
+pub mod stft;
+pub mod spectral;
+pub mod vocoder;
+pub mod envelope;
+pub mod transient;
+pub mod loudness;
+pub mod stats;
+pub mod onset;
+pub mod bpm;
+pub mod pitch;
+pub mod key;
+pub mod png;
+pub mod render;
 
 pub use reverb::reverb;
 pub use reverse::reverse;
@@ -47,14 +82,43 @@ pub use chop::chop;
 pub use overdrive::overdrive;
 pub use gain::gain;
 pub use maxgain::maxgain;
-pub use remix::remix;
+pub use remix::{remix, remix_onsets};
 pub use mosaic::mosaic;
-pub use mosaic::mosaic_with_rng;
+pub use mosaic::{mosaic_with_rng, mosaic_onsets_with_rng};
 pub use mix::mix;
 pub use silence::silence;
 pub use utils::normalize_speed;
-pub use split::split;
+pub use split::{split, split_onsets};
 pub use join::join;
+pub use convolve::convolve;
+pub use delay::delay;
+pub use chorus::chorus;
+pub use phaser::phaser;
+pub use dynamics::{compress, limit};
+pub use duck::duck;
+pub use gate::gate;
+pub use restretch::restretch;
+pub use tremolo::tremolo;
+pub use autopan::autopan;
+pub use ringmod::ringmod;
+pub use imaging::{pan, balance, width, ms_encode, ms_decode, haas, mono_check};
+pub use eq::eq;
+pub use tape::tape;
+pub use vinyl::vinyl;
+pub use tapestop::tapestop;
+pub use stutter::stutter;
+pub use granular::granular;
+pub use spectral::{spectral_freeze, spectral_blur, spectral_gate, frequency_shift, spectral_scramble};
+pub use vocoder::vocoder;
+pub use envelope::{envelope, envelope_transfer};
+pub use transient::transient;
+pub use loudness::{loudness, loudnorm};
+pub use stats::stats;
+pub use bpm::{bpm, cut_bars, normalize_tempo};
+pub use pitch::pitch;
+pub use key::key;
+pub use render::{render_waveform, render_spectrum, render_spectrogram};
+pub use onset::detect_onsets;
 
 pub use reverb::reverb_js;
 pub use reverse::reverse_js;
@@ -72,10 +136,40 @@ pub use x::x_js;
 pub use add::add_js;
 pub use chop::chop_js;
 pub use overdrive::overdrive_js;
+pub use overdrive::overdrive_with_settings_js;
 pub use gain::gain_js;
 pub use maxgain::maxgain_js;
-pub use remix::remix_js;
-pub use mosaic::mosaic_js;
+pub use remix::{remix_js, remix_onsets_js};
+pub use mosaic::{mosaic_js, mosaic_onsets_js};
 pub use mix::mix_js;
 pub use silence::silence_js;
 pub use blend::blend_js;
+pub use convolve::convolve_js;
+pub use delay::delay_js;
+pub use chorus::chorus_js;
+pub use phaser::phaser_js;
+pub use dynamics::{compress_js, limit_js};
+pub use duck::duck_js;
+pub use gate::gate_js;
+pub use restretch::restretch_js;
+pub use tremolo::tremolo_js;
+pub use autopan::autopan_js;
+pub use ringmod::ringmod_js;
+pub use imaging::{pan_js, balance_js, width_js, ms_encode_js, ms_decode_js, haas_js, mono_check_js};
+pub use eq::{eq_js, eq_response_js};
+pub use tape::tape_js;
+pub use vinyl::vinyl_js;
+pub use tapestop::tapestop_js;
+pub use stutter::stutter_js;
+pub use granular::granular_js;
+pub use spectral::{spectral_freeze_js, spectral_blur_js, spectral_gate_js, frequency_shift_js, spectral_scramble_js};
+pub use vocoder::vocoder_js;
+pub use envelope::{envelope_js, envelope_json_js, envelope_transfer_js};
+pub use transient::transient_js;
+pub use loudness::{loudness_js, loudness_curve_js, loudnorm_js};
+pub use stats::stats_js;
+pub use bpm::bpm_js;
+pub use pitch::pitch_js;
+pub use key::key_js;
+pub use render::{render_waveform_js, render_spectrum_js, render_spectrogram_js};
+pub use onset::detect_onsets_js;
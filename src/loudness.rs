@@ -0,0 +1,332 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::{get_samples, wrap_samples};
+use crate::filter::{upsample, Biquad};
+use crate::dynamics::{amplitude_to_db, db_to_amplitude, limit};
+
+// ITU-R BS.1770 block lengths, both hopped by 100 ms
+const MOMENTARY_MS: f32 = 400.0;
+const SHORT_TERM_MS: f32 = 3000.0;
+const HOP_MS: f32 = 100.0;
+const ABSOLUTE_GATE_LUFS: f32 = -70.0;
+const RELATIVE_GATE_LU: f32 = -10.0;
+// EBU Tech 3342 loudness range gate and percentiles
+const RANGE_GATE_LU: f32 = -20.0;
+const RANGE_LOW_PERCENTILE: f32 = 0.10;
+const RANGE_HIGH_PERCENTILE: f32 = 0.95;
+const TRUE_PEAK_OVERSAMPLING: usize = 4;
+
+// K-weighting: a high shelf for the head, then a high-pass (RLB curve). The
+// analog prototypes are re-derived for the sample rate, which gives the
+// coefficients printed in BS.1770 at 48 kHz.
+fn k_weighting(sample_rate: f32) -> [Biquad; 2] {
+    let rate = sample_rate as f64;
+
+    let (frequency, q, gain_db) = (1681.974450955532, 0.7071752369554193, 3.999843853973347);
+    let k = (std::f64::consts::PI * frequency / rate).tan();
+    let vh = 10.0f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad::from_coefficients(
+        (vh + vb * k / q + k * k) / a0,
+        2.0 * (k * k - vh) / a0,
+        (vh - vb * k / q + k * k) / a0,
+        2.0 * (k * k - 1.0) / a0,
+        (1.0 - k / q + k * k) / a0,
+    );
+
+    let (frequency, q) = (38.13547087613982, 0.5003270373253953);
+    let k = (std::f64::consts::PI * frequency / rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let highpass = Biquad::from_coefficients(
+        1.0,
+        -2.0,
+        1.0,
+        2.0 * (k * k - 1.0) / a0,
+        (1.0 - k / q + k * k) / a0,
+    );
+
+    [shelf, highpass]
+}
+
+// Surround channels count more, the LFE not at all (5.1 in L R C LFE Ls Rs order)
+fn channel_weight(channel: usize, channels: usize) -> f64 {
+    if channels == 6 {
+        [1.0, 1.0, 1.0, 0.0, 1.41, 1.41][channel]
+    } else {
+        1.0
+    }
+}
+
+fn energy_to_lufs(energy: f64) -> f32 {
+    if energy <= 0.0 {
+        f32::NEG_INFINITY
+    } else {
+        (-0.691 + 10.0 * energy.log10()) as f32
+    }
+}
+
+fn lufs_to_energy(lufs: f32) -> f64 {
+    10.0f64.powf((lufs as f64 + 0.691) / 10.0)
+}
+
+// Channel-weighted mean square of the K-weighted signal per hop, which the
+// block measurements are then summed from
+struct WeightedPower {
+    hops: Vec<f64>,
+    hop_frames: usize,
+    sample_rate: f32,
+}
+
+fn weighted_power(samples: &[f32], channels: usize, sample_rate: f32) -> WeightedPower {
+    let hop_frames = ((HOP_MS / 1000.0 * sample_rate) as usize).max(1);
+    let mut filters: Vec<[Biquad; 2]> = (0..channels).map(|_| k_weighting(sample_rate)).collect();
+
+    let mut hops = Vec::with_capacity(samples.len() / channels / hop_frames + 1);
+    let mut sum = 0.0f64;
+    for (i, frame) in samples.chunks(channels).enumerate() {
+        for (ch, (&sample, [shelf, highpass])) in frame.iter().zip(filters.iter_mut()).enumerate() {
+            let weighted = highpass.process(shelf.process(sample)) as f64;
+            sum += channel_weight(ch, channels) * weighted * weighted;
+        }
+        if (i + 1) % hop_frames == 0 {
+            hops.push(sum);
+            sum = 0.0;
+        }
+    }
+
+    WeightedPower { hops, hop_frames, sample_rate }
+}
+
+impl WeightedPower {
+    // Mean energy of every block of `block_ms`, hopped by 100 ms
+    fn blocks(&self, block_ms: f32) -> Vec<f64> {
+        let hops_per_block = (block_ms / HOP_MS).round() as usize;
+        if self.hops.len() < hops_per_block {
+            return Vec::new();
+        }
+        let block_frames = (hops_per_block * self.hop_frames) as f64;
+        self.hops
+            .windows(hops_per_block)
+            .map(|window| window.iter().sum::<f64>() / block_frames)
+            .collect()
+    }
+}
+
+fn integrated_loudness(blocks: &[f64]) -> f32 {
+    let absolute_gate = lufs_to_energy(ABSOLUTE_GATE_LUFS);
+    let gated: Vec<f64> = blocks.iter().copied().filter(|&e| e > absolute_gate).collect();
+    if gated.is_empty() {
+        return f32::NEG_INFINITY;
+    }
+
+    let relative_gate = lufs_to_energy(energy_to_lufs(gated.iter().sum::<f64>() / gated.len() as f64) + RELATIVE_GATE_LU);
+    let gated: Vec<f64> = gated.into_iter().filter(|&e| e > relative_gate).collect();
+    energy_to_lufs(gated.iter().sum::<f64>() / gated.len() as f64)
+}
+
+fn loudness_range(short_term_blocks: &[f64]) -> f32 {
+    let absolute_gate = lufs_to_energy(ABSOLUTE_GATE_LUFS);
+    let gated: Vec<f64> = short_term_blocks.iter().copied().filter(|&e| e > absolute_gate).collect();
+    if gated.is_empty() {
+        return 0.0;
+    }
+
+    let relative_gate = energy_to_lufs(gated.iter().sum::<f64>() / gated.len() as f64) + RANGE_GATE_LU;
+    let mut levels: Vec<f32> = gated.into_iter().map(energy_to_lufs).filter(|&l| l > relative_gate).collect();
+    levels.sort_by(|a, b| a.total_cmp(b));
+
+    let percentile = |p: f32| levels[((levels.len() - 1) as f32 * p).round() as usize];
+    percentile(RANGE_HIGH_PERCENTILE) - percentile(RANGE_LOW_PERCENTILE)
+}
+
+// Highest peak of the 4x oversampled signal, catching peaks between samples
+pub fn channel_true_peak(channel: &[f32]) -> f32 {
+    upsample(channel, TRUE_PEAK_OVERSAMPLING)
+        .iter()
+        .fold(0.0f32, |acc, s| acc.max(s.abs()))
+}
+
+fn true_peak(samples: &[f32], channels: usize) -> f32 {
+    (0..channels)
+        .map(|ch| {
+            let channel: Vec<f32> = samples.iter().skip(ch).step_by(channels).copied().collect();
+            channel_true_peak(&channel)
+        })
+        .fold(0.0f32, f32::max)
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct Loudness {
+    // Gated loudness of the whole file in LUFS
+    pub integrated: f32,
+    // Loudest 400 ms block in LUFS
+    pub momentary_max: f32,
+    // Loudest 3 s block in LUFS
+    pub short_term_max: f32,
+    // Loudness range in LU
+    pub range: f32,
+    pub true_peak_db: f32,
+    pub sample_peak_db: f32,
+}
+
+pub fn loudness(input_wav: &[u8]) -> Result<Loudness, String> {
+    let (samples, spec) = get_samples(input_wav)?;
+    let channels = spec.channels as usize;
+    let power = weighted_power(&samples, channels, spec.sample_rate as f32);
+
+    let momentary = power.blocks(MOMENTARY_MS);
+    if momentary.is_empty() {
+        return Err("Input is shorter than 400 ms, loudness cannot be measured.".to_string());
+    }
+    let short_term = power.blocks(SHORT_TERM_MS);
+    let loudest = |blocks: &[f64]| energy_to_lufs(blocks.iter().fold(0.0f64, |acc, &e| acc.max(e)));
+
+    Ok(Loudness {
+        integrated: integrated_loudness(&momentary),
+        momentary_max: loudest(&momentary),
+        short_term_max: loudest(&short_term),
+        range: loudness_range(&short_term),
+        true_peak_db: amplitude_to_db(true_peak(&samples, channels)),
+        sample_peak_db: amplitude_to_db(samples.iter().fold(0.0f32, |acc, s| acc.max(s.abs()))),
+    })
+}
+
+// Momentary (400 ms) or short-term (3 s) loudness every 100 ms, in LUFS
+pub fn loudness_curve(input_wav: &[u8], short_term: bool) -> Result<(Vec<f32>, f32), String> {
+    let (samples, spec) = get_samples(input_wav)?;
+    let power = weighted_power(&samples, spec.channels as usize, spec.sample_rate as f32);
+    let block_ms = if short_term { SHORT_TERM_MS } else { MOMENTARY_MS };
+    let curve = power.blocks(block_ms).into_iter().map(energy_to_lufs).collect();
+    Ok((curve, power.hop_frames as f32 / power.sample_rate))
+}
+
+// Gains `input_wav` to `target_lufs` integrated loudness. Peaks that would go
+// over `true_peak_db` are limited, and whatever the limiter lets through is
+// taken off the overall gain.
+pub fn loudnorm(input_wav: &[u8], target_lufs: f32, true_peak_db: f32) -> Result<Vec<u8>, String> {
+    if true_peak_db > 0.0 {
+        return Err("True peak ceiling cannot be above 0 dBTP.".to_string());
+    }
+
+    let measured = loudness(input_wav)?;
+    if !measured.integrated.is_finite() {
+        return Err("Input is too quiet to measure loudness.".to_string());
+    }
+
+    let gain_db = target_lufs - measured.integrated;
+    let mut output = if measured.true_peak_db + gain_db > true_peak_db {
+        limit(input_wav, gain_db, true_peak_db, 5.0, 80.0)?
+    } else {
+        let (mut samples, spec) = get_samples(input_wav)?;
+        let gain = db_to_amplitude(gain_db);
+        samples.iter_mut().for_each(|s| *s *= gain);
+        wrap_samples(samples, spec)?
+    };
+
+    // The limiter catches sample peaks; trim what is left between samples
+    let (mut samples, spec) = get_samples(&output)?;
+    let peak = true_peak(&samples, spec.channels as usize);
+    let ceiling = db_to_amplitude(true_peak_db);
+    if peak > ceiling {
+        let trim = ceiling / peak;
+        samples.iter_mut().for_each(|s| *s *= trim);
+        output = wrap_samples(samples, spec)?;
+    }
+
+    Ok(output)
+}
+
+#[wasm_bindgen]
+pub fn loudness_js(input_wav: &[u8]) -> Result<Loudness, JsValue> {
+    loudness(input_wav).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn loudness_curve_js(input_wav: &[u8], short_term: bool) -> Result<Vec<f32>, JsValue> {
+    loudness_curve(input_wav, short_term)
+        .map(|(curve, _)| curve)
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn loudnorm_js(input_wav: &[u8], target_lufs: f32, true_peak_db: f32) -> Result<js_sys::Uint8Array, JsValue> {
+    match loudnorm(input_wav, target_lufs, true_peak_db) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Measures the loudness of a WAV file (EBU R128)", long_about = None)]
+pub struct LoudnessArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Normalizes a WAV file to a target integrated loudness", long_about = None)]
+pub struct LoudnormArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Target integrated loudness in LUFS
+    #[arg(long, default_value_t = -14.0, allow_hyphen_values = true)]
+    pub target: f32,
+
+    /// Maximum true peak in dBTP
+    #[arg(long, default_value_t = -1.0, allow_hyphen_values = true)]
+    pub true_peak: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::get_dummy;
+    use hound::{WavSpec, SampleFormat};
+    use std::f32::consts::PI;
+
+    fn sine(frequency: f32, amplitude: f32, phase: f32, secs: f32) -> Vec<u8> {
+        let spec = WavSpec { channels: 2, sample_rate: 48000, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        let samples = (0..(48000.0 * secs) as usize)
+            .flat_map(|i| {
+                let s = amplitude * (2.0 * std::f64::consts::PI * frequency as f64 * i as f64 / 48000.0 + phase as f64).sin() as f32;
+                [s, s]
+            })
+            .collect();
+        wrap_samples(samples, spec).unwrap()
+    }
+
+    #[test]
+    fn test_reference_sine() {
+        // A stereo 997 Hz sine at -20 dBFS measures -20 LUFS
+        let measured = loudness(&sine(997.0, 0.1, 0.0, 5.0)).unwrap();
+        assert!((measured.integrated + 20.0).abs() < 0.1, "Integrated loudness was {}", measured.integrated);
+        assert!((measured.momentary_max + 20.0).abs() < 0.1);
+        assert!(measured.range < 0.1, "A steady tone has no loudness range");
+    }
+
+    #[test]
+    fn test_true_peak_between_samples() {
+        // At a quarter of the sample rate with a 45 degree phase, every sample misses the crest
+        let measured = loudness(&sine(12000.0, 0.5, PI / 4.0, 1.0)).unwrap();
+        assert!((measured.sample_peak_db - amplitude_to_db(0.5 * 0.5f32.sqrt())).abs() < 0.01);
+        assert!((measured.true_peak_db - amplitude_to_db(0.5)).abs() < 0.2, "True peak was {}", measured.true_peak_db);
+    }
+
+    #[test]
+    fn test_loudnorm() {
+        let output_wav = loudnorm(&get_dummy(), -23.0, -1.0).expect("loudnorm function failed");
+        let measured = loudness(&output_wav).unwrap();
+        assert!((measured.integrated + 23.0).abs() < 0.2, "Integrated loudness was {}", measured.integrated);
+        assert!(measured.true_peak_db <= -1.0 + 0.05);
+        assert!(loudnorm(&get_dummy(), -14.0, 1.0).is_err());
+    }
+}
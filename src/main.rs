@@ -6,7 +6,7 @@ use rand::SeedableRng;
 use w4v::reverb::{reverb, ReverbArgs};
 use w4v::maxgain::{maxgain, MaxGainArgs};
 use w4v::gain::{gain, GainArgs};
-use w4v::overdrive::{overdrive, OverdriveArgs};
+use w4v::overdrive::{overdrive_with_settings, OverdriveSettings, OverdriveArgs};
 use w4v::chop::{chop, ChopArgs};
 use w4v::add::{add, AddArgs};
 use w4v::x::{x, XArgs};
@@ -15,16 +15,50 @@ use w4v::reverse::{reverse, ReverseArgs};
 use w4v::speed::{speed, SpeedArgs};
 use w4v::len::{len, LenArgs};
 use w4v::resize::{resize, ResizeArgs};
-use w4v::flanger::{flanger, FlangerArgs};
+use w4v::flanger::{flanger_with_waveform, FlangerArgs};
 use w4v::cut::{cut, CutArgs};
 use w4v::pick::{pick, PickArgs};
 use w4v::fade::{fade, FadeArgs};
 use w4v::highpass::{highpass, HighpassArgs};
 use w4v::lowpass::{lowpass, LowpassArgs};
-use w4v::remix::{remix, RemixArgs};
-use w4v::mosaic::{mosaic, MosaicArgs};
-use w4v::blend::{blend, BlendArgs};
+use w4v::remix::{remix, remix_onsets, RemixArgs};
+use w4v::mosaic::{mosaic, mosaic_onsets_with_rng, MosaicArgs};
+use w4v::blend::{blend_with_master, pick_key_compatible, BlendArgs};
 use w4v::mix::{mix, MixArgs};
+use w4v::convolve::{convolve, ConvolveArgs};
+use w4v::delay::{delay, DelayArgs};
+use w4v::time::{resolve_ms_or_note, resolve_hz_or_note};
+use w4v::chorus::{chorus, ChorusArgs};
+use w4v::phaser::{phaser, PhaserArgs};
+use w4v::lfo::Waveform;
+use w4v::dynamics::{compress, limit, CompressorSettings, CompressArgs, LimitArgs, Detector};
+use w4v::duck::{duck, DuckSettings, DuckArgs};
+use w4v::gate::{gate, GateSettings, GateArgs};
+use w4v::restretch::{restretch, RestretchArgs};
+use w4v::tremolo::{tremolo, TremoloArgs};
+use w4v::autopan::{autopan, AutopanArgs};
+use w4v::ringmod::{ringmod, RingmodArgs};
+use w4v::imaging::{pan, balance, width, ms_encode, ms_decode, haas, mono_check};
+use w4v::imaging::{PanArgs, BalanceArgs, WidthArgs, MonoCheckArgs, MsEncodeArgs, MsDecodeArgs, HaasArgs};
+use w4v::eq::{eq, parse_bands, frequency_response, third_octave_frequencies, EqArgs};
+use w4v::tape::{tape, TapeSettings, TapeArgs};
+use w4v::vinyl::{vinyl, VinylSettings, VinylArgs};
+use w4v::tapestop::{tapestop, TapestopArgs};
+use w4v::stutter::{stutter, StutterSettings, StutterArgs};
+use w4v::granular::{granular, GranularSettings, GranularArgs};
+use w4v::spectral::{spectral_freeze, spectral_blur, spectral_gate, frequency_shift, spectral_scramble};
+use w4v::vocoder::{vocoder, VocodeArgs};
+use w4v::envelope::{envelope, envelope_points, envelope_to_csv, envelope_to_json, envelope_transfer, EnvelopeSettings, EnvelopeArgs, TransferArgs};
+use w4v::transient::{transient, TransientSettings, TransientArgs};
+use w4v::loudness::{loudness, loudnorm, LoudnessArgs, LoudnormArgs};
+use w4v::stats::{stats, stats_to_json, stats_to_text, StatsArgs};
+use w4v::bpm::{bpm, BpmArgs};
+use w4v::split::{split_onsets, SliceArgs};
+use w4v::pitch::{pitch, median_pitch, frequency_to_note, pitch_to_json, PitchArgs};
+use w4v::key::{key, key_to_json, KeyArgs, MIN_CONFIDENCE as MIN_KEY_CONFIDENCE};
+use w4v::render::{render_waveform, render_spectrum, render_spectrogram, ImageFormat, RenderSettings, RenderWaveformArgs, SpectrogramArgs};
+use w4v::spectral::{FreezeArgs, BlurArgs, SpectralGateArgs, FrequencyShiftArgs, ScrambleArgs};
+use w4v::utils::get_samples;
 
 
 #[derive(Parser)]
@@ -64,6 +98,62 @@ enum Commands {
     Mosaic(MosaicArgs),
     Blend(BlendArgs),
     Mix(MixArgs),
+    Convolve(ConvolveArgs),
+    Delay(DelayArgs),
+    Chorus(ChorusArgs),
+    Phaser(PhaserArgs),
+    Compress(CompressArgs),
+    Limit(LimitArgs),
+    Duck(DuckArgs),
+    Gate(GateArgs),
+    Restretch(RestretchArgs),
+    Tremolo(TremoloArgs),
+    Autopan(AutopanArgs),
+    Ringmod(RingmodArgs),
+    Pan(PanArgs),
+    Balance(BalanceArgs),
+    Width(WidthArgs),
+    #[command(name = "monocheck")]
+    MonoCheck(MonoCheckArgs),
+    #[command(name = "msencode")]
+    MsEncode(MsEncodeArgs),
+    #[command(name = "msdecode")]
+    MsDecode(MsDecodeArgs),
+    Haas(HaasArgs),
+    Eq(EqArgs),
+    Tape(TapeArgs),
+    Vinyl(VinylArgs),
+    Tapestop(TapestopArgs),
+    Stutter(StutterArgs),
+    Granular(GranularArgs),
+    Freeze(FreezeArgs),
+    Blur(BlurArgs),
+    #[command(name = "specgate")]
+    SpectralGate(SpectralGateArgs),
+    #[command(name = "fshift")]
+    FrequencyShift(FrequencyShiftArgs),
+    Scramble(ScrambleArgs),
+    Vocode(VocodeArgs),
+    Envelope(EnvelopeArgs),
+    Transfer(TransferArgs),
+    Transient(TransientArgs),
+    Loudness(LoudnessArgs),
+    Loudnorm(LoudnormArgs),
+    Stats(StatsArgs),
+    Bpm(BpmArgs),
+    Slice(SliceArgs),
+    Pitch(PitchArgs),
+    Key(KeyArgs),
+    RenderWaveform(RenderWaveformArgs),
+    Spectrogram(SpectrogramArgs),
+}
+
+// Seeded from --seed when given, so random results can be reproduced
+fn get_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(s) => SeedableRng::seed_from_u64(s),
+        None => SeedableRng::from_entropy(),
+    }
 }
 
 fn main() -> Result<(), String> {
@@ -107,7 +197,8 @@ fn main() -> Result<(), String> {
         Commands::Flanger(args) => {
             println!("Applying flanger to {}...", args.input);
             let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
-            let output_wav = flanger(&input_wav, args.delay, args.depth, args.rate, args.feedback)?;
+            let waveform = args.waveform.parse::<Waveform>()?;
+            let output_wav = flanger_with_waveform(&input_wav, args.delay, args.depth, args.rate, args.feedback, waveform)?;
             fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
             println!("Saved to {}", args.output);
         }
@@ -148,9 +239,8 @@ fn main() -> Result<(), String> {
         }
         Commands::Bitcrush(args) => {
             println!("Applying bitcrush effect to {}...", args.input);
-            println!("Received semitones value: {}", args.semitones); // Still semitones for now, but it's the bitcrush parameter
             let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
-            let output_wav = bitcrush(&input_wav, args.semitones)?;
+            let output_wav = bitcrush(&input_wav, args.bits, args.downsample, args.prefilter, args.dither)?;
             fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
             println!("Saved to {}", args.output);
         }
@@ -179,7 +269,16 @@ fn main() -> Result<(), String> {
         Commands::Overdrive(args) => {
             println!("Applying overdrive to {}...", args.input);
             let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
-            let output_wav = overdrive(&input_wav, args.gain, args.output_gain)?;
+            let settings = OverdriveSettings {
+                curve: args.curve.parse()?,
+                gain_db: args.gain,
+                output_gain_db: args.output_gain,
+                pre_lowcut: args.pre_lowcut,
+                post_highcut: args.post_highcut,
+                mix: args.mix,
+                oversample: args.oversample,
+            };
+            let output_wav = overdrive_with_settings(&input_wav, &settings)?;
             fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
             println!("Saved to {}", args.output);
         }
@@ -200,24 +299,29 @@ fn main() -> Result<(), String> {
         Commands::Remix(args) => {
             println!("Remixing {} with pattern '{}'...", args.input, args.pattern);
             let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
-            let output_wav = remix(&input_wav, &args.pattern)?;
+            let output_wav = if args.onsets {
+                remix_onsets(&input_wav, &args.pattern, args.sensitivity)?
+            } else {
+                remix(&input_wav, &args.pattern)?
+            };
             fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
             println!("Saved to {}", args.output);
         }
         Commands::Mosaic(args) => {
             println!("Creating mosaic of {} with pattern '{}'...", args.input, args.pattern);
             let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
-            let output_wav = mosaic(&input_wav, &args.pattern, args.segment_len)?;
+            let output_wav = if args.onsets {
+                mosaic_onsets_with_rng(&input_wav, &mut get_rng(cli.seed), &args.pattern, args.segment_len, args.sensitivity)?
+            } else {
+                mosaic(&input_wav, &args.pattern, args.segment_len)?
+            };
             fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
             println!("Saved to {}", args.output);
         },
         Commands::Blend(args) => {
             println!("Blending wavs in '{}' with '{}'...", args.input_folder, args.blender);
 
-            let mut rng: StdRng = match cli.seed {
-                Some(s) => SeedableRng::seed_from_u64(s),
-                None => SeedableRng::from_entropy(),
-            };
+            let mut rng = get_rng(cli.seed);
 
             let entries = fs::read_dir(&args.input_folder)
                 .map_err(|e| format!("Failed to read input folder: {}", e))?
@@ -230,11 +334,19 @@ fn main() -> Result<(), String> {
             }
 
             let mut samples = Vec::new();
-            for entry in entries.choose_multiple(&mut rng, 4) {
-                let wav_data = fs::read(entry.path()).map_err(|e| {
-                    format!("Failed to read WAV file '{}': {}", entry.path().display(), e)
+            if args.key_compatible {
+                samples = pick_key_compatible(entries.len(), 4, &mut rng, |i| {
+                    fs::read(entries[i].path()).map_err(|e| {
+                        format!("Failed to read WAV file '{}': {}", entries[i].path().display(), e)
+                    })
                 })?;
-                samples.push(wav_data);
+            } else {
+                for entry in entries.choose_multiple(&mut rng, 4) {
+                    let wav_data = fs::read(entry.path()).map_err(|e| {
+                        format!("Failed to read WAV file '{}': {}", entry.path().display(), e)
+                    })?;
+                    samples.push(wav_data);
+                }
             }
 
             let mut samples_refs = Vec::new();
@@ -242,7 +354,7 @@ fn main() -> Result<(), String> {
                 samples_refs.push(wav_data.as_slice());
             }
 
-            let output_wav = blend(&samples_refs, &mut rng, &args.blender, args.fx.as_deref())?;
+            let output_wav = blend_with_master(&samples_refs, &mut rng, &args.blender, args.fx.as_deref(), &args.master)?;
             fs::write(&args.output_path, output_wav)
                 .map_err(|e| format!("Failed to write output file: {}", e))?;
             println!("Saved to {}", args.output_path);
@@ -254,7 +366,479 @@ fn main() -> Result<(), String> {
             let output_wav = mix(&input_wav1, &input_wav2, args.normalize)?;
             fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
             println!("Saved to {}", args.output);
-        }        
+        }
+        Commands::Convolve(args) => {
+            println!("Convolving {} with {}...", args.input, args.ir);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let ir_wav = fs::read(&args.ir).map_err(|e| format!("Failed to read impulse response file: {}", e))?;
+            let output_wav = convolve(&input_wav, &ir_wav, args.wet, args.dry, args.predelay, args.trim.as_deref(), args.normalize)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Delay(args) => {
+            println!("Applying delay to {}...", args.input);
+            let time_ms = resolve_ms_or_note(&args.time, args.bpm)?;
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = delay(&input_wav, time_ms, args.feedback, args.ping_pong, args.lowcut, args.highcut, args.mix)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Chorus(args) => {
+            println!("Applying chorus to {}...", args.input);
+            let waveform = args.waveform.parse::<Waveform>()?;
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = chorus(&input_wav, args.voices, args.delay, args.depth, args.rate, args.spread, waveform)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Phaser(args) => {
+            println!("Applying phaser to {}...", args.input);
+            let waveform = args.waveform.parse::<Waveform>()?;
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = phaser(&input_wav, args.stages, args.rate, args.depth, args.feedback, waveform)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Compress(args) => {
+            println!("Compressing {}...", args.input);
+            let settings = CompressorSettings {
+                threshold_db: args.threshold,
+                ratio: args.ratio,
+                knee_db: args.knee,
+                attack_ms: args.attack,
+                release_ms: args.release,
+                makeup_db: args.makeup,
+                detector: args.detector.parse()?,
+                stereo_link: !args.unlinked,
+            };
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = compress(&input_wav, &settings)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Limit(args) => {
+            println!("Limiting {} to {}dBFS...", args.input, args.ceiling);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = limit(&input_wav, args.drive, args.ceiling, args.lookahead, args.release)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Duck(args) => {
+            println!("Ducking {} by {}...", args.input, args.sidechain);
+            let settings = DuckSettings {
+                threshold_db: args.threshold,
+                ratio: args.ratio,
+                depth_db: args.depth,
+                attack_ms: args.attack,
+                release_ms: args.release,
+                sidechain_lowcut: args.sc_lowcut,
+                sidechain_highcut: args.sc_highcut,
+            };
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let sidechain_wav = fs::read(&args.sidechain).map_err(|e| format!("Failed to read sidechain file: {}", e))?;
+            let output_wav = duck(&input_wav, &sidechain_wav, &settings)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Gate(args) => {
+            println!("Gating {}...", args.input);
+            let settings = GateSettings {
+                threshold_db: args.threshold,
+                hysteresis_db: args.hysteresis,
+                attack_ms: args.attack,
+                hold_ms: args.hold,
+                release_ms: args.release,
+                range_db: args.range,
+                stereo_link: !args.unlinked,
+            };
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let key_wav = match &args.key {
+                Some(key) => Some(fs::read(key).map_err(|e| format!("Failed to read key file: {}", e))?),
+                None => None,
+            };
+            let output_wav = gate(&input_wav, key_wav.as_deref(), &settings)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Restretch(args) => {
+            println!("Applying restretch effect to {}...", args.input);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = restretch(&input_wav, args.semitones)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Tremolo(args) => {
+            println!("Applying tremolo to {}...", args.input);
+            let rate_hz = resolve_hz_or_note(&args.rate, args.bpm)?;
+            let waveform = args.waveform.parse()?;
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = tremolo(&input_wav, rate_hz, args.depth, waveform)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Autopan(args) => {
+            println!("Applying auto-pan to {}...", args.input);
+            let rate_hz = resolve_hz_or_note(&args.rate, args.bpm)?;
+            let waveform = args.waveform.parse()?;
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = autopan(&input_wav, rate_hz, args.width, waveform)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Ringmod(args) => {
+            println!("Applying ring modulation to {}...", args.input);
+            let waveform = args.waveform.parse()?;
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = ringmod(&input_wav, args.carrier, waveform, args.mix)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Pan(args) => {
+            println!("Panning {}...", args.input);
+            let law = args.law.parse()?;
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = pan(&input_wav, args.position, law)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Balance(args) => {
+            println!("Balancing {}...", args.input);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = balance(&input_wav, args.balance)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Width(args) => {
+            println!("Adjusting stereo width of {}...", args.input);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = width(&input_wav, args.width)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::MonoCheck(args) => {
+            println!("Checking mono compatibility of {}...", args.input);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let check = mono_check(&input_wav)?;
+            println!("Correlation: {:.2}", check.correlation);
+            println!("Level change when summed to mono: {:.2} dB", check.mono_loss_db);
+            if check.correlation < 0.0 {
+                println!("Warning: channels are out of phase and will cancel in mono");
+            }
+        }
+        Commands::MsEncode(args) => {
+            println!("Encoding {} to mid/side...", args.input);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = ms_encode(&input_wav)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::MsDecode(args) => {
+            println!("Decoding {} from mid/side...", args.input);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = ms_decode(&input_wav)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Haas(args) => {
+            println!("Applying Haas widening to {}...", args.input);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = haas(&input_wav, args.delay)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Eq(args) => {
+            println!("Applying EQ to {}...", args.input);
+            let bands = parse_bands(&args.bands)?;
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            if args.analyze {
+                let sample_rate = get_samples(&input_wav)?.1.sample_rate as f32;
+                let frequencies = third_octave_frequencies();
+                let response = frequency_response(&bands, sample_rate, &frequencies)?;
+                println!("{:>10}  {:>8}", "Hz", "dB");
+                for (frequency, gain) in frequencies.iter().zip(response.iter()) {
+                    println!("{:>10.0}  {:>+8.2}", frequency, gain);
+                }
+            }
+            let output_wav = eq(&input_wav, &bands)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Tape(args) => {
+            println!("Applying tape character to {}...", args.input);
+            let settings = TapeSettings {
+                wow_depth_ms: args.wow,
+                wow_rate_hz: args.wow_rate,
+                flutter_depth_ms: args.flutter,
+                flutter_rate_hz: args.flutter_rate,
+                drive_db: args.drive,
+                head_bump_db: args.head_bump,
+                rolloff_hz: args.rolloff,
+                hiss_db: args.hiss,
+            };
+            let mut rng = get_rng(cli.seed);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = tape(&input_wav, &settings, &mut rng)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Vinyl(args) => {
+            println!("Applying vinyl character to {}...", args.input);
+            let settings = VinylSettings {
+                crackle_density: args.crackle,
+                pop_rate: args.pops,
+                rumble_db: args.rumble,
+                lowcut_hz: args.lowcut,
+                highcut_hz: args.highcut,
+            };
+            let mut rng = get_rng(cli.seed);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = vinyl(&input_wav, &settings, &mut rng)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Tapestop(args) => {
+            println!("Applying {} to {}...", if args.spin_up { "spin-up" } else { "tape stop" }, args.input);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = tapestop(&input_wav, &args.duration, args.curve, args.spin_up)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Stutter(args) => {
+            println!("Applying stutter to {}...", args.input);
+            let settings = StutterSettings {
+                grid: args.grid.clone(),
+                bpm: args.bpm,
+                probability: args.probability,
+                repeats: args.repeats,
+                decay: args.decay,
+                pitch_drift: args.pitch_drift,
+                gate: args.gate,
+            };
+            let mut rng = get_rng(cli.seed);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = stutter(&input_wav, &settings, &mut rng)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Granular(args) => {
+            println!("Scattering grains from {} file(s)...", args.inputs.len());
+            let settings = GranularSettings {
+                grain_ms: args.grain,
+                density: args.density,
+                position: args.position,
+                jitter: args.jitter,
+                pitch_jitter: args.pitch,
+                window: args.window.parse()?,
+                spread: args.spread,
+                duration_s: args.duration,
+            };
+            let mut rng = get_rng(cli.seed);
+            let mut sources = Vec::new();
+            for input in &args.inputs {
+                sources.push(fs::read(input).map_err(|e| format!("Failed to read input file '{}': {}", input, e))?);
+            }
+            let source_refs: Vec<&[u8]> = sources.iter().map(|s| s.as_slice()).collect();
+            let output_wav = granular(&source_refs, &settings, &mut rng)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Freeze(args) => {
+            println!("Freezing the spectrum of {}...", args.input);
+            let settings = args.stft.settings()?;
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = spectral_freeze(&input_wav, &args.at, &settings)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Blur(args) => {
+            println!("Blurring the spectrum of {}...", args.input);
+            let settings = args.stft.settings()?;
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = spectral_blur(&input_wav, args.amount, &settings)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::SpectralGate(args) => {
+            println!("Applying spectral gate to {}...", args.input);
+            let settings = args.stft.settings()?;
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = spectral_gate(&input_wav, args.threshold, &settings)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::FrequencyShift(args) => {
+            println!("Shifting frequencies of {}...", args.input);
+            let settings = args.stft.settings()?;
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = frequency_shift(&input_wav, args.shift, &settings)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Scramble(args) => {
+            println!("Scrambling the spectrum of {}...", args.input);
+            let settings = args.stft.settings()?;
+            let mut rng = get_rng(cli.seed);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = spectral_scramble(&input_wav, args.bands, &mut rng, &settings)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Vocode(args) => {
+            println!("Vocoding {} with {}...", args.carrier, args.modulator);
+            let carrier_wav = fs::read(&args.carrier).map_err(|e| format!("Failed to read carrier file: {}", e))?;
+            let modulator_wav = fs::read(&args.modulator).map_err(|e| format!("Failed to read modulator file: {}", e))?;
+            let output_wav = vocoder(&carrier_wav, &modulator_wav, args.bands, args.attack, args.release, args.formant)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Envelope(args) => {
+            let detector: Detector = args.detector.parse()?;
+            let settings = EnvelopeSettings { detector, attack_ms: args.attack, release_ms: args.release };
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let (values, sample_rate) = envelope(&input_wav, &settings)?;
+            let points = envelope_points(&values, sample_rate, args.resolution);
+            if let Some(path) = &args.csv {
+                fs::write(path, envelope_to_csv(&points)).map_err(|e| format!("Failed to write CSV file: {}", e))?;
+                println!("Saved to {}", path);
+            }
+            if let Some(path) = &args.json {
+                fs::write(path, envelope_to_json(&points)).map_err(|e| format!("Failed to write JSON file: {}", e))?;
+                println!("Saved to {}", path);
+            }
+            if args.csv.is_none() && args.json.is_none() {
+                print!("{}", envelope_to_csv(&points));
+            }
+        }
+        Commands::Transfer(args) => {
+            println!("Transferring the envelope of {} onto {}...", args.source, args.target);
+            let detector: Detector = args.detector.parse()?;
+            let settings = EnvelopeSettings { detector, attack_ms: args.attack, release_ms: args.release };
+            let source_wav = fs::read(&args.source).map_err(|e| format!("Failed to read source file: {}", e))?;
+            let target_wav = fs::read(&args.target).map_err(|e| format!("Failed to read target file: {}", e))?;
+            let output_wav = envelope_transfer(&source_wav, &target_wav, &settings, args.flatten)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Transient(args) => {
+            println!("Shaping transients of {}...", args.input);
+            let settings = TransientSettings {
+                attack_db: args.attack,
+                sustain_db: args.sustain,
+                sensitivity: args.sensitivity,
+                output_db: args.output_gain,
+                stereo_link: !args.unlinked,
+            };
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = transient(&input_wav, &settings)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Loudness(args) => {
+            println!("Measuring loudness of {}...", args.input);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let measured = loudness(&input_wav)?;
+            println!("Integrated: {:.1} LUFS", measured.integrated);
+            println!("Momentary max: {:.1} LUFS", measured.momentary_max);
+            println!("Short-term max: {:.1} LUFS", measured.short_term_max);
+            println!("Loudness range: {:.1} LU", measured.range);
+            println!("True peak: {:.1} dBTP", measured.true_peak_db);
+            println!("Sample peak: {:.1} dBFS", measured.sample_peak_db);
+        }
+        Commands::Loudnorm(args) => {
+            println!("Normalizing {} to {} LUFS...", args.input, args.target);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let output_wav = loudnorm(&input_wav, args.target, args.true_peak)?;
+            fs::write(&args.output, output_wav).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Stats(args) => {
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let stats = stats(&input_wav)?;
+            if args.json {
+                println!("{}", stats_to_json(&stats));
+            } else {
+                print!("{}", stats_to_text(&stats));
+            }
+        }
+        Commands::Bpm(args) => {
+            println!("Detecting tempo of {}...", args.input);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let tempo = bpm(&input_wav, args.min, args.max)?;
+            println!("Tempo: {:.2} BPM", tempo.bpm);
+            println!("Confidence: {:.2}", tempo.confidence);
+            if args.beats {
+                let beats: Vec<String> = tempo.beats.iter().map(|t| format!("{:.3}", t)).collect();
+                println!("Beats: {}", beats.join(" "));
+            }
+        }
+        Commands::Slice(args) => {
+            println!("Slicing {} at onsets...", args.input);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let slices = split_onsets(&input_wav, args.sensitivity)?;
+            let prefix = args.output.strip_suffix(".wav").unwrap_or(&args.output);
+            for (i, slice) in slices.iter().enumerate() {
+                let path = format!("{}_{:03}.wav", prefix, i + 1);
+                fs::write(&path, slice).map_err(|e| format!("Failed to write output file: {}", e))?;
+                println!("Saved to {}", path);
+            }
+        }
+        Commands::Pitch(args) => {
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let track = pitch(&input_wav, args.min, args.max)?;
+            if args.json {
+                println!("{}", pitch_to_json(&track));
+            } else {
+                match median_pitch(&track) {
+                    Some(frequency) => {
+                        let (note, cents) = frequency_to_note(frequency);
+                        println!("Pitch: {:.2} Hz ({} {:+.0} cents)", frequency, note, cents);
+                    }
+                    None => println!("No pitch detected"),
+                }
+            }
+        }
+        Commands::Key(args) => {
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let key = key(&input_wav)?;
+            if args.json {
+                println!("{}", key_to_json(&key));
+            } else {
+                println!("Key: {} ({})", key.name(), key.camelot());
+                println!("Confidence: {:.2}", key.confidence);
+                if key.confidence < MIN_KEY_CONFIDENCE {
+                    println!("No clear key, treat the estimate as unreliable");
+                }
+            }
+        }
+        Commands::RenderWaveform(args) => {
+            println!("Rendering waveform of {}...", args.input);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let settings = RenderSettings {
+                width: args.width,
+                height: args.height,
+                color_map: args.colormap.parse()?,
+                format: ImageFormat::from_path(&args.output),
+                ..Default::default()
+            };
+            let image = render_waveform(&input_wav, &settings)?;
+            fs::write(&args.output, image).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
+        Commands::Spectrogram(args) => {
+            println!("Rendering {} of {}...", if args.average { "spectrum" } else { "spectrogram" }, args.input);
+            let input_wav = fs::read(&args.input).map_err(|e| format!("Failed to read input file: {}", e))?;
+            let settings = RenderSettings {
+                width: args.width,
+                height: args.height,
+                color_map: args.colormap.parse()?,
+                min_db: args.min_db,
+                max_db: args.max_db,
+                format: ImageFormat::from_path(&args.output),
+            };
+            let image = if args.average { render_spectrum(&input_wav, &settings)? } else { render_spectrogram(&input_wav, &settings)? };
+            fs::write(&args.output, image).map_err(|e| format!("Failed to write output file: {}", e))?;
+            println!("Saved to {}", args.output);
+        }
     }
 
     Ok(())
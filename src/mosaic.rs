@@ -3,9 +3,11 @@ use std::collections::HashMap;
 use crate::utils::{get_samples, wrap_samples};
 use crate::pick;
 use crate::add::add;
+use crate::split::split_onsets;
 use clap::Parser;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
+use rand::seq::SliceRandom;
 
 #[derive(Parser, Debug)]
 #[command(about = "Creates a mosaic from a WAV file", long_about = None)]
@@ -25,6 +27,14 @@ pub struct MosaicArgs {
     /// Segment length in seconds
     #[arg()]
     pub segment_len: f32,
+
+    /// Start each segment on an onset instead of at a random point
+    #[arg(long)]
+    pub onsets: bool,
+
+    /// Onset sensitivity from 0 to 1 (with --onsets)
+    #[arg(long, default_value_t = 0.5)]
+    pub sensitivity: f32,
 }
 
 pub fn mosaic(
@@ -41,6 +51,36 @@ pub fn mosaic_with_rng(
     rng: &mut StdRng,
     pattern: &str,
     segment_len: f32,
+) -> Result<Vec<u8>, String> {
+    build_mosaic(input_wav_bytes, pattern, segment_len, |wav| {
+        pick::pick_with_rng(wav, rng, &segment_len.to_string())
+    })
+}
+
+// Like `mosaic_with_rng`, but each letter takes a random slice cut at an
+// onset, so every segment starts on a hit. Slices are trimmed or padded with
+// silence to `segment_len` to keep the grid steady.
+pub fn mosaic_onsets_with_rng(
+    input_wav_bytes: &[u8],
+    rng: &mut StdRng,
+    pattern: &str,
+    segment_len: f32,
+    sensitivity: f32,
+) -> Result<Vec<u8>, String> {
+    let slices = split_onsets(input_wav_bytes, sensitivity)?;
+    build_mosaic(input_wav_bytes, pattern, segment_len, |_| {
+        let slice = slices.choose(rng).ok_or("No onset slices found.")?;
+        let (mut samples, spec) = get_samples(slice)?;
+        samples.resize((spec.sample_rate as f32 * segment_len) as usize * spec.channels as usize, 0.0);
+        wrap_samples(samples, spec)
+    })
+}
+
+fn build_mosaic(
+    input_wav_bytes: &[u8],
+    pattern: &str,
+    segment_len: f32,
+    mut pick_segment: impl FnMut(&[u8]) -> Result<Vec<u8>, String>,
 ) -> Result<Vec<u8>, String> {
     let (_samples, spec) = get_samples(input_wav_bytes)?;
     let mut segments: HashMap<char, Vec<u8>> = HashMap::new();
@@ -55,7 +95,7 @@ pub fn mosaic_with_rng(
             silence.clone()
         } else {
             if !segments.contains_key(&c) {
-                let mut new_segment = pick::pick_with_rng(input_wav_bytes, rng, &segment_len.to_string())?;
+                let mut new_segment = pick_segment(input_wav_bytes)?;
                 new_segment = crate::fade(&new_segment,0.0,-30.0)?;
                 segments.insert(c, new_segment);
             }
@@ -80,6 +120,21 @@ pub fn mosaic_js(
     }
 }
 
+#[wasm_bindgen]
+pub fn mosaic_onsets_js(
+    input_wav: &[u8],
+    pattern: &str,
+    segment_len: f32,
+    sensitivity: f32,
+    seed: u64,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    match mosaic_onsets_with_rng(input_wav, &mut rng, pattern, segment_len, sensitivity) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -99,4 +154,17 @@ mod tests {
 
         assert!((output_duration - expected_duration).abs() < 0.01, "Mosaic duration is incorrect");
     }
+
+    #[test]
+    fn test_mosaic_onsets() {
+        let input_wav = get_dummy();
+        let pattern = "aab_ba";
+        let segment_len = 0.25;
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let output_wav = mosaic_onsets_with_rng(&input_wav, &mut rng, pattern, segment_len, 0.5).expect("mosaic_onsets_with_rng function failed");
+
+        let output_duration = len(&output_wav).expect("Failed to get output duration");
+        assert!((output_duration - 6.0 * segment_len).abs() < 0.01, "Mosaic duration is incorrect");
+    }
 }
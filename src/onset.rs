@@ -0,0 +1,135 @@
+use wasm_bindgen::prelude::*;
+use crate::utils::get_samples;
+use crate::stft::{stft, StftSettings};
+use crate::fft::Complex;
+use crate::window::Window;
+
+// Analysis frames of about 23 ms hopped by about 6 ms at 44.1 kHz
+const ONSET_FFT_SIZE: usize = 1024;
+const ONSET_HOP: usize = 256;
+// Log compression of the magnitudes, so quiet hits still register
+const COMPRESSION: f32 = 100.0;
+// Peak picking: a peak must be the largest within PEAK_FRAMES either side and
+// clear the average of MEAN_FRAMES either side by a sensitivity-dependent margin
+const PEAK_FRAMES: usize = 3;
+const MEAN_FRAMES: usize = 16;
+const MIN_GAP_MS: f32 = 50.0;
+
+// Onset strength (log spectral flux) of interleaved samples, one value per
+// hop. Also returns the number of values per second.
+pub fn onset_strength(samples: &[f32], channels: usize, sample_rate: u32) -> Result<(Vec<f32>, f32), String> {
+    let mono: Vec<f32> = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+
+    let settings = StftSettings { fft_size: ONSET_FFT_SIZE, hop: ONSET_HOP, window: Window::Hann };
+    let frames = stft(&mono, &settings)?;
+
+    let compressed = |frame: &Vec<Complex>| -> Vec<f32> { frame.iter().map(|bin| (1.0 + COMPRESSION * bin.norm()).ln()).collect() };
+
+    // The first frame is compared with itself, a sound that starts at full
+    // level is not an onset of the whole spectrum
+    let mut previous = frames.first().map(compressed).unwrap_or_default();
+    let strength = frames
+        .iter()
+        .map(|frame| {
+            let magnitudes = compressed(frame);
+            let flux = magnitudes.iter().zip(&previous).map(|(m, p)| (m - p).max(0.0)).sum();
+            previous = magnitudes;
+            flux
+        })
+        .collect();
+
+    Ok((strength, sample_rate as f32 / ONSET_HOP as f32))
+}
+
+// Onset times in seconds. `sensitivity` goes from 0 (only the strongest hits)
+// to 1 (every small bump). Each onset is moved back to the quiet point just
+// before the hit, so cutting there does not clip the attack.
+pub fn detect_onsets(input_wav: &[u8], sensitivity: f32) -> Result<Vec<f32>, String> {
+    if !(0.0..=1.0).contains(&sensitivity) {
+        return Err("Sensitivity must be between 0 and 1.".to_string());
+    }
+
+    let (samples, spec) = get_samples(input_wav)?;
+    let (strength, frame_rate) = onset_strength(&samples, spec.channels as usize, spec.sample_rate)?;
+    let max = strength.iter().fold(0.0f32, |acc, &v| acc.max(v));
+    if max == 0.0 {
+        return Ok(Vec::new());
+    }
+    let strength: Vec<f32> = strength.iter().map(|v| v / max).collect();
+
+    let delta = 0.02 + 0.5 * (1.0 - sensitivity);
+    let min_gap = (MIN_GAP_MS / 1000.0 * frame_rate).round() as usize;
+    let around = |i: usize, reach: usize| &strength[i.saturating_sub(reach)..(i + reach + 1).min(strength.len())];
+
+    let mut onsets: Vec<usize> = Vec::new();
+    for (i, &value) in strength.iter().enumerate() {
+        let is_peak = around(i, PEAK_FRAMES).iter().all(|&v| v <= value);
+        let local = around(i, MEAN_FRAMES);
+        let mean = local.iter().sum::<f32>() / local.len() as f32;
+        if !is_peak || value < mean + delta {
+            continue;
+        }
+
+        let mut start = i;
+        while start > 0 && strength[start - 1] < strength[start] {
+            start -= 1;
+        }
+        if onsets.last().is_none_or(|&last| start >= last + min_gap) {
+            onsets.push(start);
+        }
+    }
+
+    Ok(onsets.into_iter().map(|frame| frame as f32 / frame_rate).collect())
+}
+
+#[wasm_bindgen]
+pub fn detect_onsets_js(input_wav: &[u8], sensitivity: f32) -> Result<Vec<f32>, JsValue> {
+    detect_onsets(input_wav, sensitivity).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::wrap_samples;
+    use hound::{WavSpec, SampleFormat};
+
+    #[test]
+    fn test_onset_strength_peaks_on_hits() {
+        let sample_rate = 44100;
+        let mut samples = vec![0.0f32; sample_rate as usize];
+        // A single short burst half a second in
+        for (i, s) in samples[22050..22491].iter_mut().enumerate() {
+            *s = (i as f32 * 0.3).sin() * 0.5;
+        }
+
+        let (strength, frame_rate) = onset_strength(&samples, 1, sample_rate).unwrap();
+        let peak = strength.iter().enumerate().fold(0, |best, (i, &v)| if v > strength[best] { i } else { best });
+        assert!((peak as f32 / frame_rate - 0.5).abs() < 0.02, "The strongest onset should be on the burst");
+    }
+
+    #[test]
+    fn test_detect_onsets_before_hits() {
+        let spec = WavSpec { channels: 2, sample_rate: 44100, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        let hits = [0.3f32, 0.8, 1.1, 1.75];
+        let mut samples = vec![0.0f32; 2 * 88200];
+        for &hit in &hits {
+            let start = (hit * 44100.0) as usize;
+            for i in 0..2000 {
+                let value = (i as f32 * 0.2).sin() * (-(i as f32) / 400.0).exp();
+                samples[2 * (start + i)] = value;
+                samples[2 * (start + i) + 1] = value;
+            }
+        }
+        let wav = wrap_samples(samples, spec).unwrap();
+
+        let onsets = detect_onsets(&wav, 0.5).expect("detect_onsets function failed");
+        assert_eq!(onsets.len(), hits.len(), "Onsets found: {:?}", onsets);
+        for (onset, hit) in onsets.iter().zip(hits) {
+            assert!(*onset <= hit && hit - onset < 0.03, "Onset {} should fall just before the hit at {}", onset, hit);
+        }
+        assert!(detect_onsets(&wav, 1.5).is_err());
+    }
+}
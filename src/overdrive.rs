@@ -1,31 +1,141 @@
 use wasm_bindgen::prelude::*;
 use clap::Parser;
-use crate::utils::{get_samples, clamp_samples, wrap_samples};
+use std::f32::consts::PI;
+use std::str::FromStr;
+use crate::utils::{get_samples, wrap_samples};
+use crate::filter::{upsample, downsample, OnePoleHighpass, OnePoleLowpass};
 
 // Helper function from fade.rs
 fn db_to_amplitude(db: f32) -> f32 {
     10.0_f32.powf(db / 20.0)
 }
 
+// Bias of the asymmetric tube curve (adds even harmonics)
+const TUBE_BIAS: f32 = 0.2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Curve {
+    Tanh,
+    HardClip,
+    Tube,     // Asymmetric soft clipping
+    Foldback, // Triangle wavefolder
+    SineFold,
+    Rectify,  // Full-wave rectification
+}
+
+impl FromStr for Curve {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tanh" => Ok(Curve::Tanh),
+            "hard" => Ok(Curve::HardClip),
+            "tube" => Ok(Curve::Tube),
+            "foldback" => Ok(Curve::Foldback),
+            "sinefold" => Ok(Curve::SineFold),
+            "rectify" => Ok(Curve::Rectify),
+            _ => Err(format!("Curve not recognized: {}", s)),
+        }
+    }
+}
+
+pub fn get_curve_list() -> Vec<String> {
+    ["tanh", "hard", "tube", "foldback", "sinefold", "rectify"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+impl Curve {
+    pub fn shape(&self, x: f32) -> f32 {
+        match self {
+            Curve::Tanh => x.tanh(),
+            Curve::HardClip => x.clamp(-1.0, 1.0),
+            Curve::Tube => ((x + TUBE_BIAS).tanh() - TUBE_BIAS.tanh()) / (1.0 + TUBE_BIAS.tanh()),
+            Curve::Foldback => 1.0 - ((x + 1.0).rem_euclid(4.0) - 2.0).abs(),
+            Curve::SineFold => (x * PI / 2.0).sin(),
+            Curve::Rectify => x.abs().tanh(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct OverdriveSettings {
+    pub curve: Curve,
+    pub gain_db: f32,
+    pub output_gain_db: f32,
+    // Highpass before the nonlinearity (tightens the low end)
+    pub pre_lowcut: Option<f32>,
+    // Lowpass after the nonlinearity (tames fizz)
+    pub post_highcut: Option<f32>,
+    // Dry/wet balance (0.0 dry, 1.0 wet)
+    pub mix: f32,
+    // Oversampling factor around the nonlinearity (1, 2, 4 or 8)
+    pub oversample: u32,
+}
+
+impl Default for OverdriveSettings {
+    fn default() -> Self {
+        OverdriveSettings {
+            curve: Curve::Tanh,
+            gain_db: 10.0,
+            output_gain_db: 0.0,
+            pre_lowcut: None,
+            post_highcut: None,
+            mix: 1.0,
+            oversample: 1,
+        }
+    }
+}
+
 pub fn overdrive(input_wav: &[u8], gain_db: f32, output_gain_db: f32) -> Result<Vec<u8>, String> {
-    let (mut samples, spec) = get_samples(input_wav)?;
+    let settings = OverdriveSettings { gain_db, output_gain_db, ..Default::default() };
+    overdrive_with_settings(input_wav, &settings)
+}
+
+pub fn overdrive_with_settings(input_wav: &[u8], settings: &OverdriveSettings) -> Result<Vec<u8>, String> {
+    if ![1, 2, 4, 8].contains(&settings.oversample) {
+        return Err("Oversampling factor must be 1, 2, 4 or 8.".to_string());
+    }
+
+    let (samples, spec) = get_samples(input_wav)?;
+    let sample_rate = spec.sample_rate as f32;
+    let channels = spec.channels as usize;
+    let factor = settings.oversample as usize;
 
-    let input_amplitude = db_to_amplitude(gain_db);
-    let output_amplitude = db_to_amplitude(output_gain_db);
+    let input_amplitude = db_to_amplitude(settings.gain_db);
+    let output_amplitude = db_to_amplitude(settings.output_gain_db);
 
-    for sample in samples.iter_mut() {
-        // Apply input gain
-        let mut processed_sample = *sample * input_amplitude;
+    let mut output = vec![0.0; samples.len()];
 
-        // Apply soft clipping (tanh function)
-        processed_sample = processed_sample.tanh();
+    for ch in 0..channels {
+        let dry: Vec<f32> = samples.iter().skip(ch).step_by(channels).cloned().collect();
 
-        // Apply output gain
-        *sample = processed_sample * output_amplitude;
+        let mut driven = dry.clone();
+        if let Some(cutoff) = settings.pre_lowcut {
+            let mut highpass = OnePoleHighpass::new(cutoff, sample_rate);
+            driven.iter_mut().for_each(|s| *s = highpass.process(*s));
+        }
+
+        // The nonlinearity runs at the oversampled rate so its harmonics
+        // can be filtered out before they fold back as aliasing
+        let mut shaped = upsample(&driven, factor);
+        for sample in shaped.iter_mut() {
+            *sample = settings.curve.shape(*sample * input_amplitude);
+        }
+        let mut wet = downsample(&shaped, factor);
+
+        if let Some(cutoff) = settings.post_highcut {
+            let mut lowpass = OnePoleLowpass::new(cutoff, sample_rate);
+            wet.iter_mut().for_each(|s| *s = lowpass.process(*s));
+        }
+
+        for (i, (d, w)) in dry.iter().zip(wet.iter()).enumerate() {
+            output[i * channels + ch] = (d * (1.0 - settings.mix) + w * settings.mix) * output_amplitude;
+        }
     }
 
-    clamp_samples(&mut samples);
-    wrap_samples(samples, spec)
+    wrap_samples(output, spec)
 }
 
 #[wasm_bindgen]
@@ -40,6 +150,28 @@ pub fn overdrive_js(
     }
 }
 
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn overdrive_with_settings_js(
+    input_wav: &[u8],
+    curve: &str,
+    gain_db: f32,
+    output_gain_db: f32,
+    pre_lowcut: Option<f32>,
+    post_highcut: Option<f32>,
+    mix: f32,
+    oversample: u32,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = curve.parse::<Curve>().and_then(|curve| {
+        let settings = OverdriveSettings { curve, gain_db, output_gain_db, pre_lowcut, post_highcut, mix, oversample };
+        overdrive_with_settings(input_wav, &settings)
+    });
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(about = "Applies an overdrive effect to a WAV file", long_about = None)]
 pub struct OverdriveArgs {
@@ -58,6 +190,26 @@ pub struct OverdriveArgs {
     /// Output gain in dB (e.g., -3.0 for 3dB attenuation after distortion)
     #[arg(allow_hyphen_values = true)]
     pub output_gain: f32,
+
+    /// Waveshaping curve (tanh, hard, tube, foldback, sinefold, rectify)
+    #[arg(long, default_value = "tanh")]
+    pub curve: String,
+
+    /// Low cut (highpass) frequency before the distortion, in Hz
+    #[arg(long)]
+    pub pre_lowcut: Option<f32>,
+
+    /// High cut (lowpass) frequency after the distortion, in Hz
+    #[arg(long)]
+    pub post_highcut: Option<f32>,
+
+    /// Dry/wet mix (0.0 dry to 1.0 wet)
+    #[arg(long, default_value_t = 1.0)]
+    pub mix: f32,
+
+    /// Oversampling factor around the distortion (1, 2, 4 or 8)
+    #[arg(long, default_value_t = 1)]
+    pub oversample: u32,
 }
 
 #[cfg(test)]
@@ -80,4 +232,45 @@ mod tests {
         // Further checks could involve analyzing sample values for clipping/distortion
         // For now, just ensure it doesn't panic and produces output.
     }
+
+    #[test]
+    fn test_curves_are_bounded() {
+        for name in get_curve_list() {
+            let curve: Curve = name.parse().unwrap();
+            for i in -100..=100 {
+                let y = curve.shape(i as f32 * 0.37);
+                assert!(y.abs() <= 1.0 + 1e-6, "{} produced {} out of range", name, y);
+            }
+            assert!(curve.shape(0.0).abs() < 1e-6, "{} should map silence to silence", name);
+        }
+    }
+
+    #[test]
+    fn test_overdrive_with_settings() {
+        let input_wav_bytes = get_dummy();
+        let settings = OverdriveSettings {
+            curve: Curve::Foldback,
+            gain_db: 30.0,
+            output_gain_db: -6.0,
+            pre_lowcut: Some(100.0),
+            post_highcut: Some(6000.0),
+            mix: 0.8,
+            oversample: 4,
+        };
+
+        let output_wav_bytes = overdrive_with_settings(&input_wav_bytes, &settings)
+            .expect("overdrive_with_settings function failed");
+
+        let (input_samples, _) = get_samples(&input_wav_bytes).unwrap();
+        let (output_samples, _) = get_samples(&output_wav_bytes).unwrap();
+        assert_eq!(input_samples.len(), output_samples.len(), "Oversampling should not change the length");
+        assert_ne!(input_wav_bytes, output_wav_bytes, "Output WAV content should be different from input WAV content");
+    }
+
+    #[test]
+    fn test_overdrive_rejects_invalid_oversampling() {
+        let input_wav_bytes = get_dummy();
+        let settings = OverdriveSettings { oversample: 3, ..Default::default() };
+        assert!(overdrive_with_settings(&input_wav_bytes, &settings).is_err(), "3x oversampling should be rejected");
+    }
 }
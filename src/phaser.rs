@@ -0,0 +1,154 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use std::f32::consts::PI;
+use crate::utils::{get_samples, wrap_samples, clamp_samples};
+use crate::lfo::{Lfo, Waveform};
+
+// Lowest frequency of the allpass sweep
+const MIN_SWEEP_HZ: f32 = 200.0;
+// Octaves covered by the sweep at full depth
+const SWEEP_OCTAVES: f32 = 4.0;
+
+// First-order allpass section
+#[derive(Clone, Copy, Default)]
+struct Allpass {
+    x_prev: f32,
+    y_prev: f32,
+}
+
+impl Allpass {
+    fn process(&mut self, x: f32, coefficient: f32) -> f32 {
+        let y = coefficient * x + self.x_prev - coefficient * self.y_prev;
+        self.x_prev = x;
+        self.y_prev = y;
+        y
+    }
+}
+
+pub fn phaser(
+    input_wav: &[u8],
+    stages: u32,        // Number of cascaded allpass stages (2 to 12)
+    rate_hz: f32,       // LFO rate in Hz
+    depth: f32,         // Sweep depth (0.0 to 1.0)
+    feedback: f32,      // Feedback amount (-1.0 to 1.0, exclusive)
+    waveform: Waveform, // LFO shape
+) -> Result<Vec<u8>, String> {
+    if !(2..=12).contains(&stages) {
+        return Err("Phaser stages must be between 2 and 12.".to_string());
+    }
+    if feedback.abs() >= 1.0 {
+        return Err("Feedback must be between -1.0 and 1.0 (exclusive).".to_string());
+    }
+
+    let (samples, spec) = get_samples(input_wav)?;
+
+    let sample_rate = spec.sample_rate as f32;
+    let channels = spec.channels as usize;
+    let max_sweep_hz = (MIN_SWEEP_HZ * 2.0f32.powf(depth.clamp(0.0, 1.0) * SWEEP_OCTAVES)).min(sample_rate * 0.45);
+
+    let mut allpasses = vec![vec![Allpass::default(); stages as usize]; channels];
+    let mut last_outputs = vec![0.0f32; channels];
+    // Channels sweep a quarter cycle apart for stereo movement
+    let mut lfos: Vec<Lfo> = (0..channels)
+        .map(|ch| Lfo::with_phase(waveform, rate_hz, sample_rate, ch as f32 * 0.25))
+        .collect();
+
+    let mut output_samples = Vec::with_capacity(samples.len());
+
+    for frame in samples.chunks(channels) {
+        for (ch, &input_sample) in frame.iter().enumerate() {
+            let sweep_hz = MIN_SWEEP_HZ * (max_sweep_hz / MIN_SWEEP_HZ).powf(lfos[ch].next_unipolar());
+            let t = (PI * sweep_hz / sample_rate).tan();
+            let coefficient = (t - 1.0) / (t + 1.0);
+
+            let mut phased = input_sample + feedback * last_outputs[ch];
+            for stage in allpasses[ch].iter_mut() {
+                phased = stage.process(phased, coefficient);
+            }
+            last_outputs[ch] = phased;
+
+            // Summing with the dry signal turns the phase shifts into notches
+            output_samples.push(0.5 * input_sample + 0.5 * phased);
+        }
+    }
+
+    clamp_samples(&mut output_samples);
+    wrap_samples(output_samples, spec)
+}
+
+#[wasm_bindgen]
+pub fn phaser_js(
+    input_wav: &[u8],
+    stages: u32,
+    rate_hz: f32,
+    depth: f32,
+    feedback: f32,
+    waveform: &str,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = waveform
+        .parse::<Waveform>()
+        .and_then(|waveform| phaser(input_wav, stages, rate_hz, depth, feedback, waveform));
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Applies a phaser effect to a WAV file", long_about = None)]
+pub struct PhaserArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Number of allpass stages (2 to 12)
+    #[arg(long, default_value_t = 4)]
+    pub stages: u32,
+
+    /// LFO rate in Hz
+    #[arg(long, default_value_t = 0.5)]
+    pub rate: f32,
+
+    /// Sweep depth (0.0 to 1.0)
+    #[arg(long, default_value_t = 0.7)]
+    pub depth: f32,
+
+    /// Feedback amount (-1.0 to 1.0)
+    #[arg(long, default_value_t = 0.5, allow_hyphen_values = true)]
+    pub feedback: f32,
+
+    /// LFO waveform (sine, triangle, square, saw, random)
+    #[arg(long, default_value = "sine")]
+    pub waveform: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+
+    #[test]
+    fn test_phaser_effect() {
+        let input_wav = get_dummy();
+
+        let output_wav = phaser(&input_wav, 6, 0.5, 0.8, 0.5, Waveform::Saw)
+            .expect("phaser function failed");
+
+        let original_duration = len(&input_wav).unwrap();
+        let processed_duration = len(&output_wav).unwrap();
+        assert_eq!(original_duration, processed_duration, "Phaser should not change the duration");
+        assert_ne!(input_wav, output_wav, "Phaser should modify the audio content");
+    }
+
+    #[test]
+    fn test_phaser_rejects_invalid_stages() {
+        let input_wav = get_dummy();
+        assert!(phaser(&input_wav, 1, 0.5, 0.8, 0.5, Waveform::Sine).is_err(), "A single stage should be rejected");
+        assert!(phaser(&input_wav, 16, 0.5, 0.8, 0.5, Waveform::Sine).is_err(), "Too many stages should be rejected");
+    }
+}
@@ -0,0 +1,201 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::get_samples;
+use crate::fft::{ifft, next_pow2, real_fft};
+
+// YIN analysis window and hop, in samples
+const WINDOW: usize = 2048;
+const HOP: usize = 512;
+// Dips of the normalized difference below this count as periodic
+const THRESHOLD: f32 = 0.15;
+// Frames quieter than this are unvoiced (about -50 dBFS RMS)
+const SILENCE_RMS: f32 = 0.003;
+
+pub const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+#[derive(Clone, Copy, Debug)]
+pub struct PitchPoint {
+    pub time: f32,
+    // Fundamental frequency in Hz, 0 when unvoiced
+    pub frequency: f32,
+    // How periodic the frame is, 0..1
+    pub clarity: f32,
+}
+
+// Cumulative mean normalized difference function of YIN over lags
+// 0..=max_lag of `segment`, which must hold WINDOW + max_lag samples
+fn normalized_difference(segment: &[f32], max_lag: usize) -> Vec<f32> {
+    // Correlation of the window with the whole segment, through the FFT
+    let size = next_pow2(WINDOW + max_lag);
+    let window = real_fft(&segment[..WINDOW], size);
+    let mut correlation = real_fft(segment, size);
+    for (c, w) in correlation.iter_mut().zip(&window) {
+        *c = w.conj() * *c;
+    }
+    ifft(&mut correlation);
+
+    let mut energy = vec![0.0f32; segment.len() + 1];
+    for (i, &s) in segment.iter().enumerate() {
+        energy[i + 1] = energy[i] + s * s;
+    }
+
+    let mut difference = vec![1.0f32; max_lag + 1];
+    let mut running_sum = 0.0f32;
+    for lag in 1..=max_lag {
+        let d = (energy[WINDOW] + energy[lag + WINDOW] - energy[lag] - 2.0 * correlation[lag].re).max(0.0);
+        running_sum += d;
+        difference[lag] = if running_sum > 0.0 { d * lag as f32 / running_sum } else { 1.0 };
+    }
+    difference
+}
+
+// Monophonic f0 track with the YIN algorithm, one point every 512 samples
+pub fn pitch(input_wav: &[u8], min_hz: f32, max_hz: f32) -> Result<Vec<PitchPoint>, String> {
+    let (samples, spec) = get_samples(input_wav)?;
+    let sample_rate = spec.sample_rate as f32;
+    if min_hz <= 0.0 || max_hz <= min_hz || max_hz >= sample_rate / 2.0 {
+        return Err("Pitch range must be increasing and below the Nyquist frequency.".to_string());
+    }
+
+    let channels = spec.channels as usize;
+    let mono: Vec<f32> = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+
+    let min_lag = ((sample_rate / max_hz).floor() as usize).max(2);
+    let max_lag = (sample_rate / min_hz).ceil() as usize;
+    let mut segment = vec![0.0f32; WINDOW + max_lag];
+
+    let mut track = Vec::new();
+    let mut start = 0;
+    while start < mono.len() {
+        let available = (mono.len() - start).min(segment.len());
+        segment[..available].copy_from_slice(&mono[start..start + available]);
+        segment[available..].iter_mut().for_each(|s| *s = 0.0);
+
+        let time = (start + WINDOW / 2) as f32 / sample_rate;
+        let rms = (segment[..WINDOW].iter().map(|s| s * s).sum::<f32>() / WINDOW as f32).sqrt();
+        let mut point = PitchPoint { time, frequency: 0.0, clarity: 0.0 };
+
+        if rms >= SILENCE_RMS {
+            let difference = normalized_difference(&segment, max_lag);
+            if let Some(mut lag) = (min_lag..max_lag).find(|&lag| difference[lag] < THRESHOLD) {
+                while lag + 1 < max_lag && difference[lag + 1] < difference[lag] {
+                    lag += 1;
+                }
+                let (left, centre, right) = (difference[lag - 1], difference[lag], difference[lag + 1]);
+                let curvature = left - 2.0 * centre + right;
+                let offset = if curvature > 0.0 { (0.5 * (left - right) / curvature).clamp(-0.5, 0.5) } else { 0.0 };
+
+                point.frequency = sample_rate / (lag as f32 + offset);
+                point.clarity = (1.0 - centre).clamp(0.0, 1.0);
+            }
+        }
+
+        track.push(point);
+        start += HOP;
+    }
+
+    Ok(track)
+}
+
+// Median frequency of the voiced frames, if there are any
+pub fn median_pitch(track: &[PitchPoint]) -> Option<f32> {
+    let mut voiced: Vec<f32> = track.iter().map(|p| p.frequency).filter(|&f| f > 0.0).collect();
+    if voiced.is_empty() {
+        return None;
+    }
+    voiced.sort_by(|a, b| a.total_cmp(b));
+    Some(voiced[voiced.len() / 2])
+}
+
+// Nearest equal-tempered note (A4 = 440 Hz) and the offset from it in cents
+pub fn frequency_to_note(frequency: f32) -> (String, f32) {
+    let semitones = 12.0 * (frequency / 440.0).log2() + 69.0;
+    let midi = semitones.round();
+    let cents = (semitones - midi) * 100.0;
+    let name = NOTE_NAMES[(midi as i32).rem_euclid(12) as usize];
+    (format!("{}{}", name, (midi as i32).div_euclid(12) - 1), cents)
+}
+
+pub fn pitch_to_json(track: &[PitchPoint]) -> String {
+    let median = match median_pitch(track) {
+        Some(frequency) => {
+            let (note, cents) = frequency_to_note(frequency);
+            format!("{{\"frequency\":{:.2},\"note\":\"{}\",\"cents\":{:.1}}}", frequency, note, cents)
+        }
+        None => "null".to_string(),
+    };
+    let points: Vec<String> = track
+        .iter()
+        .map(|p| format!("{{\"time\":{:.4},\"frequency\":{:.2},\"clarity\":{:.3}}}", p.time, p.frequency, p.clarity))
+        .collect();
+    format!("{{\"median\":{},\"track\":[{}]}}", median, points.join(","))
+}
+
+// The f0 curve, one value per 512 samples (0 where unvoiced)
+#[wasm_bindgen]
+pub fn pitch_js(input_wav: &[u8], min_hz: f32, max_hz: f32) -> Result<Vec<f32>, JsValue> {
+    match pitch(input_wav, min_hz, max_hz) {
+        Ok(track) => Ok(track.iter().map(|p| p.frequency).collect()),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Tracks the pitch of a monophonic WAV file", long_about = None)]
+pub struct PitchArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Lowest frequency considered in Hz
+    #[arg(long, default_value_t = 50.0)]
+    pub min: f32,
+
+    /// Highest frequency considered in Hz
+    #[arg(long, default_value_t = 1000.0)]
+    pub max: f32,
+
+    /// Print the pitch track as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::wrap_samples;
+    use hound::{WavSpec, SampleFormat};
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_pitch_of_harmonic_tone() {
+        let spec = WavSpec { channels: 1, sample_rate: 44100, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        // 220 Hz with a strong second harmonic, which should not fool the tracker
+        let samples: Vec<f32> = (0..22050)
+            .map(|i| {
+                let t = i as f32 / 44100.0;
+                0.3 * (2.0 * PI * 220.0 * t).sin() + 0.4 * (2.0 * PI * 440.0 * t).sin()
+            })
+            .collect();
+        let wav = wrap_samples(samples, spec).unwrap();
+
+        let track = pitch(&wav, 50.0, 1000.0).expect("pitch function failed");
+        let median = median_pitch(&track).unwrap();
+        assert!((median - 220.0).abs() < 1.0, "Detected {} Hz", median);
+        assert_eq!(frequency_to_note(median).0, "A3");
+        assert!(pitch(&wav, 500.0, 100.0).is_err());
+    }
+
+    #[test]
+    fn test_silence_is_unvoiced() {
+        let spec = WavSpec { channels: 2, sample_rate: 44100, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        let wav = wrap_samples(vec![0.0; 44100], spec).unwrap();
+
+        let track = pitch(&wav, 50.0, 1000.0).unwrap();
+        assert!(median_pitch(&track).is_none());
+        assert!(pitch_to_json(&track).starts_with("{\"median\":null,"));
+    }
+}
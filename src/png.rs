@@ -0,0 +1,102 @@
+// Minimal PNG encoder for 8-bit RGB images. The image data is stored
+// uncompressed inside the zlib stream, which every decoder accepts.
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+// Largest payload of a stored deflate block
+const MAX_STORED_BLOCK: usize = 65535;
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = if c & 1 != 0 { 0xEDB88320 ^ (c >> 1) } else { c >> 1 };
+        }
+        *entry = c;
+    }
+    !bytes.iter().fold(!0u32, |crc, &b| table[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in bytes.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = if data.is_empty() { vec![data] } else { data.chunks(MAX_STORED_BLOCK).collect() };
+    for (i, block) in blocks.iter().enumerate() {
+        out.push(if i + 1 == blocks.len() { 1 } else { 0 });
+        let len = block.len() as u16;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+// `pixels` holds width * height RGB triples, row by row from the top
+pub fn encode_png(width: usize, height: usize, pixels: &[[u8; 3]]) -> Result<Vec<u8>, String> {
+    if width == 0 || height == 0 || pixels.len() != width * height {
+        return Err("Image size does not match its pixels.".to_string());
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // 8 bits per channel, truecolor, default compression, filter and no interlace
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    // Every row starts with filter type 0 (none)
+    let mut raw = Vec::with_capacity(height * (1 + 3 * width));
+    for row in pixels.chunks(width) {
+        raw.push(0);
+        raw.extend(row.iter().flatten());
+    }
+
+    let mut png = SIGNATURE.to_vec();
+    push_chunk(&mut png, b"IHDR", &header);
+    push_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    push_chunk(&mut png, b"IEND", &[]);
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E60398);
+    }
+
+    #[test]
+    fn test_encode_png() {
+        let png = encode_png(200, 200, &vec![[255, 0, 0]; 40000]).unwrap();
+        assert_eq!(png[..8], SIGNATURE);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+        // 200 rows of 601 bytes need two stored blocks
+        assert_eq!(png.len(), 8 + 25 + (12 + 2 + 2 * 5 + 200 * 601 + 4) + 12);
+        assert!(encode_png(2, 2, &[[0, 0, 0]; 3]).is_err());
+    }
+}
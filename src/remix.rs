@@ -1,7 +1,7 @@
 use wasm_bindgen::prelude::*;
 use clap::Parser;
 use crate::add::add;
-use crate::split::split;
+use crate::split::{split, split_onsets};
 
 pub fn remix(input_wav: &[u8], pattern: &str) -> Result<Vec<u8>, String> {
     let num_segments = pattern.chars().count();
@@ -9,6 +9,26 @@ pub fn remix(input_wav: &[u8], pattern: &str) -> Result<Vec<u8>, String> {
         return Err("Pattern cannot be empty.".to_string());
     }
 
+    let pattern_indices = parse_pattern(pattern, num_segments)?;
+    let segments = split(input_wav, num_segments)?;
+
+    join_segments(&segments, &pattern_indices)
+}
+
+// Like `remix`, but the pattern digits pick slices cut at onsets, so hits are
+// never cut in half. Slices past the ninth cannot be addressed.
+pub fn remix_onsets(input_wav: &[u8], pattern: &str, sensitivity: f32) -> Result<Vec<u8>, String> {
+    if pattern.is_empty() {
+        return Err("Pattern cannot be empty.".to_string());
+    }
+
+    let segments = split_onsets(input_wav, sensitivity)?;
+    let pattern_indices = parse_pattern(pattern, segments.len().min(9))?;
+
+    join_segments(&segments, &pattern_indices)
+}
+
+fn parse_pattern(pattern: &str, num_segments: usize) -> Result<Vec<usize>, String> {
     let mut pattern_indices: Vec<usize> = Vec::new();
     for c in pattern.chars() {
         let digit = c.to_digit(10).ok_or_else(|| format!("Invalid character in pattern: {}", c))?;
@@ -24,8 +44,10 @@ pub fn remix(input_wav: &[u8], pattern: &str) -> Result<Vec<u8>, String> {
         }
     }
 
-    let segments = split(input_wav, num_segments)?;
+    Ok(pattern_indices)
+}
 
+fn join_segments(segments: &[Vec<u8>], pattern_indices: &[usize]) -> Result<Vec<u8>, String> {
     if segments.is_empty() {
         return Err("Splitting the audio resulted in no segments.".to_string());
     }
@@ -50,6 +72,18 @@ pub fn remix_js(
     }
 }
 
+#[wasm_bindgen]
+pub fn remix_onsets_js(
+    input_wav: &[u8],
+    pattern: &str,
+    sensitivity: f32,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    match remix_onsets(input_wav, pattern, sensitivity) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(about = "Remixes a WAV file based on a pattern", long_about = None)]
 pub struct RemixArgs {
@@ -64,6 +98,14 @@ pub struct RemixArgs {
     /// Remix pattern (e.g., "1213")
     #[arg()]
     pub pattern: String,
+
+    /// Use slices cut at onsets instead of equal segments
+    #[arg(long)]
+    pub onsets: bool,
+
+    /// Onset sensitivity from 0 to 1 (with --onsets)
+    #[arg(long, default_value_t = 0.5)]
+    pub sensitivity: f32,
 }
 
 #[cfg(test)]
@@ -124,6 +166,17 @@ mod tests {
         assert!(result.is_err(), "Should fail with index 0");
     }
 
+    #[test]
+    fn test_remix_onsets() {
+        let input_wav_bytes = get_dummy();
+        let slices = split_onsets(&input_wav_bytes, 0.5).unwrap();
+
+        let output_wav_bytes = remix_onsets(&input_wav_bytes, "2211", 0.5).expect("remix_onsets function failed");
+        let expected = 2.0 * (len(&slices[0]).unwrap() + len(&slices[1]).unwrap());
+
+        assert!((len(&output_wav_bytes).unwrap() - expected).abs() < 1e-3, "Output should be made of whole onset slices");
+    }
+
     #[test]
     fn test_remix_empty_pattern() {
         let input_wav_bytes = get_dummy();
@@ -0,0 +1,490 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use std::str::FromStr;
+use crate::utils::get_samples;
+use crate::stft::{stft, StftSettings};
+use crate::window::Window;
+use crate::dynamics::amplitude_to_db;
+use crate::png::encode_png;
+
+// Frequency analysis frames, fine enough to separate low notes
+const FFT_SIZE: usize = 4096;
+// Lowest frequency on the log frequency axis
+const MIN_FREQUENCY: f32 = 20.0;
+// Largest image side, to keep the pixel buffer reasonable
+const MAX_SIDE: usize = 8192;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorMap {
+    Magma,
+    Viridis,
+    Gray,
+}
+
+impl FromStr for ColorMap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "magma" => Ok(ColorMap::Magma),
+            "viridis" => Ok(ColorMap::Viridis),
+            "gray" => Ok(ColorMap::Gray),
+            _ => Err(format!("Color map not recognized: {}", s)),
+        }
+    }
+}
+
+impl ColorMap {
+    // Color at `position` from 0.0 (quiet) to 1.0 (loud)
+    pub fn color(&self, position: f32) -> [u8; 3] {
+        let stops: &[[f32; 3]] = match self {
+            ColorMap::Magma => &[[0.0, 0.0, 4.0], [81.0, 18.0, 124.0], [183.0, 55.0, 121.0], [252.0, 137.0, 97.0], [252.0, 253.0, 191.0]],
+            ColorMap::Viridis => &[[68.0, 1.0, 84.0], [59.0, 82.0, 139.0], [33.0, 145.0, 140.0], [94.0, 201.0, 98.0], [253.0, 231.0, 37.0]],
+            ColorMap::Gray => &[[0.0, 0.0, 0.0], [255.0, 255.0, 255.0]],
+        };
+        let scaled = position.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+        let index = (scaled as usize).min(stops.len() - 2);
+        let t = scaled - index as f32;
+        std::array::from_fn(|c| (stops[index][c] + t * (stops[index + 1][c] - stops[index][c])).round() as u8)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Svg,
+}
+
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "png" => Ok(ImageFormat::Png),
+            "svg" => Ok(ImageFormat::Svg),
+            _ => Err(format!("Image format not recognized: {}", s)),
+        }
+    }
+}
+
+impl ImageFormat {
+    // SVG for paths ending in .svg, PNG otherwise
+    pub fn from_path(path: &str) -> Self {
+        if path.to_lowercase().ends_with(".svg") { ImageFormat::Svg } else { ImageFormat::Png }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RenderSettings {
+    pub width: usize,
+    pub height: usize,
+    pub color_map: ColorMap,
+    // Levels mapped to the two ends of the color map (spectrum and spectrogram)
+    pub min_db: f32,
+    pub max_db: f32,
+    pub format: ImageFormat,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings { width: 1200, height: 400, color_map: ColorMap::Magma, min_db: -90.0, max_db: 0.0, format: ImageFormat::Png }
+    }
+}
+
+impl RenderSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_SIDE).contains(&self.width) || !(1..=MAX_SIDE).contains(&self.height) {
+            return Err(format!("Image width and height must be between 1 and {}.", MAX_SIDE));
+        }
+        if self.min_db >= self.max_db {
+            return Err("Minimum dB must be below maximum dB.".to_string());
+        }
+        Ok(())
+    }
+
+    // Position of a level in the dB range, 0..1
+    fn level(&self, db: f32) -> f32 {
+        ((db - self.min_db) / (self.max_db - self.min_db)).clamp(0.0, 1.0)
+    }
+}
+
+fn hex(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn svg_document(settings: &RenderSettings, body: &str) -> Vec<u8> {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n<rect width=\"{w}\" height=\"{h}\" fill=\"{}\"/>\n{}</svg>\n",
+        hex(settings.color_map.color(0.0)),
+        body,
+        w = settings.width,
+        h = settings.height
+    )
+    .into_bytes()
+}
+
+// Closed outline through `top` left to right, then `bottom` right to left
+fn svg_band(top: &[(f32, f32)], bottom: &[(f32, f32)], color: [u8; 3]) -> String {
+    let points: Vec<String> = top.iter().chain(bottom.iter().rev()).map(|(x, y)| format!("{:.1},{:.1}", x, y)).collect();
+    format!("<polygon points=\"{}\" fill=\"{}\"/>\n", points.join(" "), hex(color))
+}
+
+fn to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
+    samples.chunks(channels).map(|frame| frame.iter().sum::<f32>() / channels as f32).collect()
+}
+
+// Magnitude spectra in dBFS, where a full-scale sine reads 0 dB
+fn spectra_db(mono: &[f32], hop: usize) -> Result<Vec<Vec<f32>>, String> {
+    let settings = StftSettings { fft_size: FFT_SIZE, hop, window: Window::Hann };
+    let scale = 2.0 / settings.window.coefficients(FFT_SIZE).iter().sum::<f32>();
+    Ok(stft(mono, &settings)?
+        .iter()
+        .map(|frame| frame.iter().map(|bin| amplitude_to_db(bin.norm() * scale)).collect())
+        .collect())
+}
+
+// Fractional FFT bin shown at `position` (0 bottom, 1 top) of a log frequency axis
+fn bin_at(position: f32, sample_rate: f32) -> f32 {
+    let nyquist = sample_rate / 2.0;
+    let frequency = MIN_FREQUENCY * (nyquist / MIN_FREQUENCY).powf(position);
+    frequency * FFT_SIZE as f32 / sample_rate
+}
+
+// Bin ranges covered by `count` pixels along the log frequency axis, low to high
+fn pixel_bins(count: usize, sample_rate: f32) -> Vec<(f32, f32)> {
+    (0..count)
+        .map(|i| {
+            let position = |offset: f32| (i as f32 + offset) / count as f32;
+            (bin_at(position(0.0), sample_rate), bin_at(position(1.0), sample_rate))
+        })
+        .collect()
+}
+
+// Loudest bin within `low..high`, or the level interpolated at its centre when
+// the range falls between two bins
+fn band_level(values: &[f32], (low, high): (f32, f32)) -> f32 {
+    let last = values.len() - 1;
+    let (first, end) = (low.ceil() as usize, (high.floor() as usize).min(last));
+    if first <= end {
+        values[first..=end].iter().fold(f32::NEG_INFINITY, |acc, &v| acc.max(v))
+    } else {
+        let centre = (0.5 * (low + high)).min(last as f32);
+        let index = (centre as usize).min(last - 1);
+        let t = centre - index as f32;
+        values[index] + t * (values[index + 1] - values[index])
+    }
+}
+
+// Min/max peaks and RMS of each channel per image column, channels stacked
+// top to bottom
+pub fn render_waveform(input_wav: &[u8], settings: &RenderSettings) -> Result<Vec<u8>, String> {
+    settings.validate()?;
+    let (samples, spec) = get_samples(input_wav)?;
+    let channels = spec.channels as usize;
+    let frames = samples.len() / channels;
+    let (width, height) = (settings.width, settings.height);
+    let lane = height as f32 / channels as f32;
+
+    // (min, max, rms) per channel and column
+    let columns: Vec<Vec<(f32, f32, f32)>> = (0..channels)
+        .map(|ch| {
+            (0..width)
+                .map(|x| {
+                    let start = x * frames / width;
+                    let end = ((x + 1) * frames / width).max(start + 1).min(frames);
+                    let values: Vec<f32> = (start..end).map(|i| samples[i * channels + ch]).collect();
+                    if values.is_empty() {
+                        return (0.0, 0.0, 0.0);
+                    }
+                    let min = values.iter().fold(f32::MAX, |acc, &v| acc.min(v));
+                    let max = values.iter().fold(f32::MIN, |acc, &v| acc.max(v));
+                    let rms = (values.iter().map(|v| v * v).sum::<f32>() / values.len() as f32).sqrt();
+                    (min, max, rms)
+                })
+                .collect()
+        })
+        .collect();
+
+    let peak_color = settings.color_map.color(0.6);
+    let rms_color = settings.color_map.color(0.9);
+    let y_of = |ch: usize, value: f32| (ch as f32 + 0.5 - 0.5 * value.clamp(-1.0, 1.0)) * lane;
+
+    match settings.format {
+        ImageFormat::Png => {
+            let mut pixels = vec![settings.color_map.color(0.0); width * height];
+            for (ch, column) in columns.iter().enumerate() {
+                for (x, &(min, max, rms)) in column.iter().enumerate() {
+                    for (low, high, color) in [(min, max, peak_color), (-rms, rms, rms_color)] {
+                        let top = y_of(ch, high).floor() as usize;
+                        let bottom = (y_of(ch, low).ceil() as usize).max(top + 1).min(height);
+                        for y in top..bottom {
+                            pixels[y * width + x] = color;
+                        }
+                    }
+                }
+            }
+            encode_png(width, height, &pixels)
+        }
+        ImageFormat::Svg => {
+            let mut body = String::new();
+            for (ch, column) in columns.iter().enumerate() {
+                let x_of = |x: usize| x as f32 + 0.5;
+                let band = |low: &dyn Fn(&(f32, f32, f32)) -> f32, high: &dyn Fn(&(f32, f32, f32)) -> f32, color| {
+                    let top: Vec<(f32, f32)> = column.iter().enumerate().map(|(x, c)| (x_of(x), y_of(ch, high(c)))).collect();
+                    let bottom: Vec<(f32, f32)> = column.iter().enumerate().map(|(x, c)| (x_of(x), y_of(ch, low(c)))).collect();
+                    svg_band(&top, &bottom, color)
+                };
+                body.push_str(&band(&|c| c.0, &|c| c.1, peak_color));
+                body.push_str(&band(&|c| -c.2, &|c| c.2, rms_color));
+            }
+            Ok(svg_document(settings, &body))
+        }
+    }
+}
+
+// Spectrum averaged over the whole file, on a log frequency axis
+pub fn render_spectrum(input_wav: &[u8], settings: &RenderSettings) -> Result<Vec<u8>, String> {
+    settings.validate()?;
+    let (samples, spec) = get_samples(input_wav)?;
+    let mono = to_mono(&samples, spec.channels as usize);
+    let spectra = spectra_db(&mono, FFT_SIZE / 4)?;
+
+    // Power average across frames
+    let bins = FFT_SIZE / 2 + 1;
+    let average: Vec<f32> = (0..bins)
+        .map(|bin| {
+            let power = spectra.iter().map(|frame| 10.0f32.powf(frame[bin] / 10.0)).sum::<f32>() / spectra.len() as f32;
+            10.0 * power.max(1e-20).log10()
+        })
+        .collect();
+
+    let (width, height) = (settings.width, settings.height);
+    let levels: Vec<f32> = pixel_bins(width, spec.sample_rate as f32)
+        .into_iter()
+        .map(|range| settings.level(band_level(&average, range)))
+        .collect();
+
+    match settings.format {
+        ImageFormat::Png => {
+            let mut pixels = vec![settings.color_map.color(0.0); width * height];
+            for (x, &level) in levels.iter().enumerate() {
+                let filled = (level * height as f32).round() as usize;
+                for y in height - filled..height {
+                    // Shaded by height, so louder regions stand out
+                    pixels[y * width + x] = settings.color_map.color(0.25 + 0.75 * (height - y) as f32 / height as f32);
+                }
+            }
+            encode_png(width, height, &pixels)
+        }
+        ImageFormat::Svg => {
+            let top: Vec<(f32, f32)> = levels.iter().enumerate().map(|(x, l)| (x as f32, (1.0 - l) * height as f32)).collect();
+            let bottom = vec![(0.0, height as f32), ((width - 1) as f32, height as f32)];
+            Ok(svg_document(settings, &svg_band(&top, &bottom, settings.color_map.color(0.7))))
+        }
+    }
+}
+
+// Time left to right, log frequency bottom to top, level as color. The SVG
+// version embeds the same picture as a PNG.
+pub fn render_spectrogram(input_wav: &[u8], settings: &RenderSettings) -> Result<Vec<u8>, String> {
+    settings.validate()?;
+    let (samples, spec) = get_samples(input_wav)?;
+    let mono = to_mono(&samples, spec.channels as usize);
+    let (width, height) = (settings.width, settings.height);
+
+    // About one frame per column, overlapping at least by half
+    let hop = (mono.len() / width).clamp(64, FFT_SIZE / 2);
+    let spectra = spectra_db(&mono, hop)?;
+
+    // Top row first
+    let bins: Vec<(f32, f32)> = pixel_bins(height, spec.sample_rate as f32).into_iter().rev().collect();
+    let columns: Vec<Vec<f32>> = (0..width)
+        .map(|x| {
+            // Loudest of the frames that fall in this column
+            let start = x * spectra.len() / width;
+            let end = ((x + 1) * spectra.len() / width).max(start + 1);
+            bins.iter()
+                .map(|&range| spectra[start..end].iter().map(|frame| band_level(frame, range)).fold(f32::NEG_INFINITY, f32::max))
+                .collect()
+        })
+        .collect();
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for column in &columns {
+            pixels.push(settings.color_map.color(settings.level(column[y])));
+        }
+    }
+    let png = encode_png(width, height, &pixels)?;
+
+    match settings.format {
+        ImageFormat::Png => Ok(png),
+        ImageFormat::Svg => {
+            let body = format!(
+                "<image width=\"{}\" height=\"{}\" preserveAspectRatio=\"none\" href=\"data:image/png;base64,{}\"/>\n",
+                width,
+                height,
+                base64(&png)
+            );
+            Ok(svg_document(settings, &body))
+        }
+    }
+}
+
+fn js_settings(width: usize, height: usize, color_map: &str, min_db: f32, max_db: f32, format: &str) -> Result<RenderSettings, String> {
+    Ok(RenderSettings { width, height, color_map: color_map.parse()?, min_db, max_db, format: format.parse()? })
+}
+
+#[wasm_bindgen]
+pub fn render_waveform_js(input_wav: &[u8], width: usize, height: usize, color_map: &str, format: &str) -> Result<js_sys::Uint8Array, JsValue> {
+    let defaults = RenderSettings::default();
+    let result = js_settings(width, height, color_map, defaults.min_db, defaults.max_db, format)
+        .and_then(|settings| render_waveform(input_wav, &settings));
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[wasm_bindgen]
+pub fn render_spectrum_js(input_wav: &[u8], width: usize, height: usize, color_map: &str, min_db: f32, max_db: f32, format: &str) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = js_settings(width, height, color_map, min_db, max_db, format)
+        .and_then(|settings| render_spectrum(input_wav, &settings));
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[wasm_bindgen]
+pub fn render_spectrogram_js(input_wav: &[u8], width: usize, height: usize, color_map: &str, min_db: f32, max_db: f32, format: &str) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = js_settings(width, height, color_map, min_db, max_db, format)
+        .and_then(|settings| render_spectrogram(input_wav, &settings));
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Renders the waveform of a WAV file to a PNG or SVG image", long_about = None)]
+pub struct RenderWaveformArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output image (.png or .svg)
+    #[arg()]
+    pub output: String,
+
+    /// Image width in pixels
+    #[arg(long, default_value_t = 1200)]
+    pub width: usize,
+
+    /// Image height in pixels
+    #[arg(long, default_value_t = 400)]
+    pub height: usize,
+
+    /// Color map (magma, viridis, gray)
+    #[arg(long, default_value = "magma")]
+    pub colormap: String,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Renders a spectrogram or averaged spectrum of a WAV file to a PNG or SVG image", long_about = None)]
+pub struct SpectrogramArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output image (.png or .svg)
+    #[arg()]
+    pub output: String,
+
+    /// Image width in pixels
+    #[arg(long, default_value_t = 1200)]
+    pub width: usize,
+
+    /// Image height in pixels
+    #[arg(long, default_value_t = 400)]
+    pub height: usize,
+
+    /// Color map (magma, viridis, gray)
+    #[arg(long, default_value = "magma")]
+    pub colormap: String,
+
+    /// Level shown at the bottom of the color map in dBFS
+    #[arg(long, default_value_t = -90.0, allow_hyphen_values = true)]
+    pub min_db: f32,
+
+    /// Level shown at the top of the color map in dBFS
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    pub max_db: f32,
+
+    /// Plot the spectrum averaged over the whole file instead
+    #[arg(long)]
+    pub average: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::wrap_samples;
+    use hound::{WavSpec, SampleFormat};
+    use std::f32::consts::PI;
+
+    fn sine(frequency: f32, amplitude: f32) -> Vec<u8> {
+        let spec = WavSpec { channels: 1, sample_rate: 44100, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        let samples = (0..44100).map(|i| amplitude * (2.0 * PI * frequency * i as f32 / 44100.0).sin()).collect();
+        wrap_samples(samples, spec).unwrap()
+    }
+
+    #[test]
+    fn test_spectrogram_shows_tone() {
+        let settings = RenderSettings { width: 10, height: 100, color_map: ColorMap::Gray, ..Default::default() };
+        let png = render_spectrogram(&sine(1000.0, 0.5), &settings).unwrap();
+        assert_eq!(png.len(), 8 + 25 + (12 + 2 + 5 + 100 * 31 + 4) + 12);
+
+        // The stored rows can be read straight back: the brightest row of the
+        // middle column should sit at 1 kHz on the log axis
+        let raw = &png[8 + 25 + 8 + 2 + 5..];
+        let row_of = |y: usize| raw[y * 31 + 1 + 5 * 3];
+        let brightest = (0..100).max_by_key(|&y| row_of(y)).unwrap();
+        let expected = 100.0 * (1.0 - (1000.0f32 / 20.0).ln() / (22050.0f32 / 20.0).ln()) - 0.5;
+        assert!((brightest as f32 - expected).abs() <= 2.0, "Tone at row {}, expected {}", brightest, expected);
+        // -6 dBFS sine at -90..0 dB
+        assert!((row_of(brightest) as f32 - 255.0 * 84.0 / 90.0).abs() < 8.0, "Level {}", row_of(brightest));
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let wav = sine(440.0, 0.8);
+        let svg_settings = RenderSettings { width: 64, height: 32, format: ImageFormat::Svg, ..Default::default() };
+        for render in [render_waveform, render_spectrum, render_spectrogram] {
+            let svg = String::from_utf8(render(&wav, &svg_settings).unwrap()).unwrap();
+            assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+            let png = render(&wav, &RenderSettings { format: ImageFormat::Png, ..svg_settings }).unwrap();
+            assert_eq!(&png[1..4], b"PNG");
+        }
+        assert!(render_waveform(&wav, &RenderSettings { width: 0, ..Default::default() }).is_err());
+        assert!(render_spectrogram(&wav, &RenderSettings { min_db: 0.0, max_db: -10.0, ..Default::default() }).is_err());
+        assert_eq!(ImageFormat::from_path("out.SVG"), ImageFormat::Svg);
+        assert_eq!(base64(b"w4v!"), "dzR2IQ==");
+    }
+}
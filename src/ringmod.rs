@@ -0,0 +1,102 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::{get_samples, wrap_samples};
+use crate::lfo::{Lfo, Waveform};
+
+pub fn ringmod(
+    input_wav: &[u8],
+    carrier_hz: f32,     // Carrier frequency in Hz (sub-audio rates turn into tremolo)
+    waveform: Waveform,  // Carrier shape
+    mix: f32,            // Dry/wet mix (0.0 dry to 1.0 wet)
+) -> Result<Vec<u8>, String> {
+    if carrier_hz <= 0.0 {
+        return Err("Carrier frequency must be positive.".to_string());
+    }
+    if !(0.0..=1.0).contains(&mix) {
+        return Err("Mix must be between 0.0 and 1.0.".to_string());
+    }
+
+    let (mut samples, spec) = get_samples(input_wav)?;
+    let channels = spec.channels as usize;
+
+    // The LFO runs happily at audio rate, so it doubles as the carrier
+    let mut carrier = Lfo::new(waveform, carrier_hz, spec.sample_rate as f32);
+
+    for frame in samples.chunks_mut(channels) {
+        let c = carrier.next_value();
+        for sample in frame.iter_mut() {
+            *sample = *sample * (1.0 - mix) + *sample * c * mix;
+        }
+    }
+
+    wrap_samples(samples, spec)
+}
+
+#[wasm_bindgen]
+pub fn ringmod_js(
+    input_wav: &[u8],
+    carrier_hz: f32,
+    waveform: &str,
+    mix: f32,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = waveform
+        .parse::<Waveform>()
+        .and_then(|waveform| ringmod(input_wav, carrier_hz, waveform, mix));
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Applies ring modulation to a WAV file", long_about = None)]
+pub struct RingmodArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Carrier frequency in Hz
+    #[arg()]
+    pub carrier: f32,
+
+    /// Carrier waveform (sine, triangle, square, saw, random)
+    #[arg(long, default_value = "sine")]
+    pub waveform: String,
+
+    /// Dry/wet mix (0.0 to 1.0)
+    #[arg(long, default_value_t = 1.0)]
+    pub mix: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+    use hound::{WavSpec, SampleFormat};
+
+    #[test]
+    fn test_ringmod_effect() {
+        let input_wav = get_dummy();
+
+        let output_wav = ringmod(&input_wav, 440.0, Waveform::Sine, 0.7).expect("ringmod function failed");
+
+        assert_eq!(len(&input_wav).unwrap(), len(&output_wav).unwrap(), "Ring modulation should not change the duration");
+        assert_ne!(input_wav, output_wav, "Ring modulation should modify the audio content");
+    }
+
+    #[test]
+    fn test_ringmod_square_carrier_flips_polarity() {
+        let spec = WavSpec { channels: 1, sample_rate: 4, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        let input_wav = wrap_samples(vec![0.5; 4], spec).unwrap();
+
+        let output_wav = ringmod(&input_wav, 1.0, Waveform::Square, 1.0).unwrap();
+        let (output, _) = get_samples(&output_wav).unwrap();
+
+        assert_eq!(output, vec![0.5, 0.5, -0.5, -0.5], "A square carrier should flip the polarity every half cycle");
+    }
+}
@@ -0,0 +1,353 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use std::f32::consts::PI;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use crate::utils::{get_samples, wrap_samples};
+use crate::fft::Complex;
+use crate::stft::{stft, istft, StftSettings, StftArgs};
+use crate::time::resolve_time;
+use crate::dynamics::db_to_amplitude;
+
+type Frames = Vec<Vec<Complex>>;
+
+// Runs `process` on the STFT frames of every channel and resynthesizes
+fn process_spectral<F>(input_wav: &[u8], settings: &StftSettings, mut process: F) -> Result<Vec<u8>, String>
+where
+    F: FnMut(&mut Frames, f32) -> Result<(), String>,
+{
+    settings.validate()?;
+    let (samples, spec) = get_samples(input_wav)?;
+    let channels = spec.channels as usize;
+    let frames_len = samples.len() / channels;
+    let mut output = vec![0.0f32; samples.len()];
+
+    for ch in 0..channels {
+        let channel: Vec<f32> = samples.iter().skip(ch).step_by(channels).cloned().collect();
+        let mut frames = stft(&channel, settings)?;
+        process(&mut frames, spec.sample_rate as f32)?;
+        let restored = istft(&frames, settings, frames_len)?;
+        for (i, sample) in restored.into_iter().enumerate() {
+            output[i * channels + ch] = sample;
+        }
+    }
+
+    wrap_samples(output, spec)
+}
+
+// Phase a bin advances by in one hop when its frequency sits exactly on the bin
+fn expected_phase_advance(bin: f32, settings: &StftSettings) -> f32 {
+    2.0 * PI * bin * settings.hop as f32 / settings.fft_size as f32
+}
+
+// Moves bins by `offset`, rotating their phase so they stay coherent from frame to frame
+fn move_bin(bin: Complex, offset: isize, frame: usize, settings: &StftSettings) -> Complex {
+    let rotation = expected_phase_advance(offset as f32, settings) * frame as f32;
+    bin * Complex::from_polar(1.0, rotation)
+}
+
+// Holds the spectrum found at `at` (seconds or fraction) until the end
+pub fn spectral_freeze(input_wav: &[u8], at: &str, settings: &StftSettings) -> Result<Vec<u8>, String> {
+    let (samples, spec) = get_samples(input_wav)?;
+    let duration = samples.len() as f32 / spec.channels as f32 / spec.sample_rate as f32;
+    let at_seconds = resolve_time(at, duration)?;
+    let freeze_frame = (at_seconds * spec.sample_rate as f32 / settings.hop as f32).round() as usize;
+
+    process_spectral(input_wav, settings, |frames, _| {
+        let Some(frozen) = frames.get(freeze_frame).cloned() else {
+            return Err("Freeze point is beyond the end of the WAV file.".to_string());
+        };
+        let mut phases: Vec<f32> = frozen.iter().map(|b| b.arg()).collect();
+        for frame in frames.iter_mut().skip(freeze_frame + 1) {
+            for (bin, (value, phase)) in frame.iter_mut().zip(phases.iter_mut()).enumerate() {
+                *phase += expected_phase_advance(bin as f32, settings);
+                *value = Complex::from_polar(frozen[bin].norm(), *phase);
+            }
+        }
+        Ok(())
+    })
+}
+
+// Smears magnitudes over time; `amount` from 0.0 (none) towards 1.0 (endless)
+pub fn spectral_blur(input_wav: &[u8], amount: f32, settings: &StftSettings) -> Result<Vec<u8>, String> {
+    if !(0.0..1.0).contains(&amount) {
+        return Err("Blur amount must be at least 0.0 and below 1.0.".to_string());
+    }
+
+    process_spectral(input_wav, settings, |frames, _| {
+        let mut smoothed = vec![0.0f32; settings.bins()];
+        for frame in frames.iter_mut() {
+            for (value, level) in frame.iter_mut().zip(smoothed.iter_mut()) {
+                *level = amount * *level + (1.0 - amount) * value.norm();
+                *value = Complex::from_polar(*level, value.arg());
+            }
+        }
+        Ok(())
+    })
+}
+
+// Drops every bin quieter than `threshold_db` (dBFS of a sine in that bin)
+pub fn spectral_gate(input_wav: &[u8], threshold_db: f32, settings: &StftSettings) -> Result<Vec<u8>, String> {
+    // A full-scale sine peaks at half the window's sum
+    let window_gain: f32 = settings.window.coefficients(settings.fft_size).iter().sum::<f32>() / 2.0;
+    let threshold = db_to_amplitude(threshold_db) * window_gain;
+
+    process_spectral(input_wav, settings, |frames, _| {
+        for value in frames.iter_mut().flatten() {
+            if value.norm() < threshold {
+                *value = Complex::default();
+            }
+        }
+        Ok(())
+    })
+}
+
+// Shifts every frequency up or down by the same number of Hz (not harmonic, unlike pitch shifting)
+pub fn frequency_shift(input_wav: &[u8], shift_hz: f32, settings: &StftSettings) -> Result<Vec<u8>, String> {
+    process_spectral(input_wav, settings, |frames, sample_rate| {
+        let bin_width = sample_rate / settings.fft_size as f32;
+        let offset = (shift_hz / bin_width).round() as isize;
+        let bins = settings.bins() as isize;
+
+        for (k, frame) in frames.iter_mut().enumerate() {
+            let mut shifted = vec![Complex::default(); frame.len()];
+            for (bin, value) in frame.iter().enumerate() {
+                let target = bin as isize + offset;
+                if (1..bins).contains(&target) {
+                    shifted[target as usize] = move_bin(*value, offset, k, settings);
+                }
+            }
+            *frame = shifted;
+        }
+        Ok(())
+    })
+}
+
+// Swaps `bands` equal slices of the spectrum around, in a random order fixed for the whole file
+pub fn spectral_scramble(input_wav: &[u8], bands: usize, rng: &mut StdRng, settings: &StftSettings) -> Result<Vec<u8>, String> {
+    let band_size = (settings.bins() - 1) / bands.max(1);
+    if bands < 2 || band_size == 0 {
+        return Err("Scrambling needs at least 2 bands, each at least one bin wide.".to_string());
+    }
+    let mut order: Vec<usize> = (0..bands).collect();
+    order.shuffle(rng);
+
+    process_spectral(input_wav, settings, |frames, _| {
+        for (k, frame) in frames.iter_mut().enumerate() {
+            let mut scrambled = frame.clone();
+            for (source, &target) in order.iter().enumerate() {
+                // Bin 0 (DC) is left alone
+                let (from, to) = (1 + source * band_size, 1 + target * band_size);
+                let offset = to as isize - from as isize;
+                for i in 0..band_size {
+                    scrambled[to + i] = move_bin(frame[from + i], offset, k, settings);
+                }
+            }
+            *frame = scrambled;
+        }
+        Ok(())
+    })
+}
+
+fn settings_js(fft_size: usize, hop: usize, window: &str) -> Result<StftSettings, String> {
+    Ok(StftSettings { fft_size, hop, window: window.parse()? })
+}
+
+#[wasm_bindgen]
+pub fn spectral_freeze_js(input_wav: &[u8], at: &str, fft_size: usize, hop: usize, window: &str) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = settings_js(fft_size, hop, window).and_then(|settings| spectral_freeze(input_wav, at, &settings));
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[wasm_bindgen]
+pub fn spectral_blur_js(input_wav: &[u8], amount: f32, fft_size: usize, hop: usize, window: &str) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = settings_js(fft_size, hop, window).and_then(|settings| spectral_blur(input_wav, amount, &settings));
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[wasm_bindgen]
+pub fn spectral_gate_js(input_wav: &[u8], threshold_db: f32, fft_size: usize, hop: usize, window: &str) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = settings_js(fft_size, hop, window).and_then(|settings| spectral_gate(input_wav, threshold_db, &settings));
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[wasm_bindgen]
+pub fn frequency_shift_js(input_wav: &[u8], shift_hz: f32, fft_size: usize, hop: usize, window: &str) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = settings_js(fft_size, hop, window).and_then(|settings| frequency_shift(input_wav, shift_hz, &settings));
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[wasm_bindgen]
+pub fn spectral_scramble_js(input_wav: &[u8], bands: usize, seed: u64, fft_size: usize, hop: usize, window: &str) -> Result<js_sys::Uint8Array, JsValue> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let result = settings_js(fft_size, hop, window).and_then(|settings| spectral_scramble(input_wav, bands, &mut rng, &settings));
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Freezes the spectrum of a WAV file from a point in time", long_about = None)]
+pub struct FreezeArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Freeze point in seconds (can be absolute or fraction like "1/2")
+    #[arg(default_value = "1/2")]
+    pub at: String,
+
+    #[command(flatten)]
+    pub stft: StftArgs,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Smears the spectrum of a WAV file over time", long_about = None)]
+pub struct BlurArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Blur amount (0.0 to just below 1.0)
+    #[arg(default_value_t = 0.9)]
+    pub amount: f32,
+
+    #[command(flatten)]
+    pub stft: StftArgs,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Removes quiet frequency bins from a WAV file", long_about = None)]
+pub struct SpectralGateArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Threshold in dBFS
+    #[arg(default_value_t = -50.0, allow_hyphen_values = true)]
+    pub threshold: f32,
+
+    #[command(flatten)]
+    pub stft: StftArgs,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Shifts all frequencies of a WAV file by a fixed amount", long_about = None)]
+pub struct FrequencyShiftArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Shift in Hz (negative shifts down)
+    #[arg(allow_hyphen_values = true)]
+    pub shift: f32,
+
+    #[command(flatten)]
+    pub stft: StftArgs,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Shuffles frequency bands of a WAV file", long_about = None)]
+pub struct ScrambleArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Number of bands to shuffle
+    #[arg(long, default_value_t = 16)]
+    pub bands: usize,
+
+    #[command(flatten)]
+    pub stft: StftArgs,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cut::cut;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+    use crate::fft::real_fft;
+    use hound::{WavSpec, SampleFormat};
+
+    fn sine_wav(frequency: f32) -> Vec<u8> {
+        let spec = WavSpec { channels: 1, sample_rate: 8000, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        let samples = (0..8000).map(|i| (2.0 * PI * frequency * i as f32 / 8000.0).sin() * 0.5).collect();
+        wrap_samples(samples, spec).unwrap()
+    }
+
+    fn peak_hz(wav: &[u8]) -> f32 {
+        let (samples, spec) = get_samples(wav).unwrap();
+        let spectrum = real_fft(&samples[2000..6096], 4096);
+        let peak = (1..2048).max_by(|&a, &b| spectrum[a].norm().partial_cmp(&spectrum[b].norm()).unwrap()).unwrap();
+        peak as f32 * spec.sample_rate as f32 / 4096.0
+    }
+
+    #[test]
+    fn test_frequency_shift_moves_peak() {
+        let settings = StftSettings { fft_size: 1024, hop: 256, ..Default::default() };
+        let output_wav = frequency_shift(&sine_wav(500.0), 250.0, &settings).expect("frequency_shift function failed");
+        assert!((peak_hz(&output_wav) - 750.0).abs() < 10.0, "A 500 Hz sine shifted by 250 Hz should peak at 750 Hz");
+    }
+
+    #[test]
+    fn test_spectral_gate_and_freeze() {
+        let settings = StftSettings::default();
+        let quiet = spectral_gate(&sine_wav(500.0), -3.0, &settings).unwrap();
+        let (samples, _) = get_samples(&quiet).unwrap();
+        assert!(samples.iter().all(|s| s.abs() < 1e-3), "A -6 dBFS sine should be gated at -3 dBFS");
+
+        let input_wav = cut(&get_dummy(), "0", "1").unwrap();
+        let frozen = spectral_freeze(&input_wav, "1/4", &settings).expect("spectral_freeze function failed");
+        assert_eq!(len(&input_wav).unwrap(), len(&frozen).unwrap(), "Freezing should not change the duration");
+        assert!(spectral_freeze(&input_wav, "5.0", &settings).is_err(), "Freezing past the end should fail");
+    }
+
+    #[test]
+    fn test_blur_and_scramble() {
+        let settings = StftSettings { fft_size: 512, hop: 128, ..Default::default() };
+        let input_wav = cut(&get_dummy(), "0", "1").unwrap();
+
+        let blurred = spectral_blur(&input_wav, 0.8, &settings).expect("spectral_blur function failed");
+        assert_ne!(blurred, input_wav, "Blur should modify the audio content");
+
+        let a = spectral_scramble(&input_wav, 8, &mut StdRng::seed_from_u64(2), &settings).unwrap();
+        let b = spectral_scramble(&input_wav, 8, &mut StdRng::seed_from_u64(2), &settings).unwrap();
+        assert_eq!(a, b, "The same seed should give the same result");
+        assert!(spectral_scramble(&input_wav, 1, &mut StdRng::seed_from_u64(2), &settings).is_err());
+    }
+}
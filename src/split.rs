@@ -1,4 +1,6 @@
+use clap::Parser;
 use crate::utils::{get_samples, wrap_samples};
+use crate::onset::detect_onsets;
 
 pub fn split(input_wav: &[u8], n: usize) -> Result<Vec<Vec<u8>>, String> {
     if n == 1 {
@@ -34,6 +36,46 @@ pub fn split(input_wav: &[u8], n: usize) -> Result<Vec<Vec<u8>>, String> {
     Ok(segments)
 }
 
+// Splits at detected onsets instead of into equal pieces. The first slice
+// starts at zero, so any lead-in before the first hit is kept.
+pub fn split_onsets(input_wav: &[u8], sensitivity: f32) -> Result<Vec<Vec<u8>>, String> {
+    let onsets = detect_onsets(input_wav, sensitivity)?;
+    let (samples, spec) = get_samples(input_wav)?;
+    let channels = spec.channels as usize;
+    let total_frames = samples.len() / channels;
+
+    let mut boundaries = vec![0];
+    for onset in onsets {
+        let frame = ((onset * spec.sample_rate as f32) as usize).min(total_frames);
+        if frame > *boundaries.last().unwrap() {
+            boundaries.push(frame);
+        }
+    }
+    boundaries.push(total_frames);
+
+    boundaries
+        .windows(2)
+        .filter(|bounds| bounds[1] > bounds[0])
+        .map(|bounds| wrap_samples(samples[bounds[0] * channels..bounds[1] * channels].to_vec(), spec))
+        .collect()
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Slices a WAV file at its onsets into numbered files", long_about = None)]
+pub struct SliceArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output path prefix (slices are saved as <prefix>_001.wav, ...)
+    #[arg()]
+    pub output: String,
+
+    /// Onset sensitivity from 0 (strongest hits only) to 1 (every bump)
+    #[arg(long, default_value_t = 0.5)]
+    pub sensitivity: f32,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,4 +125,15 @@ mod tests {
         assert_eq!(segments.len(), n, "Should produce 1 segment");
         assert_eq!(segments[0], input_wav_bytes, "The single segment should be identical to the original");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_split_onsets_covers_input() {
+        let input_wav_bytes = get_dummy();
+
+        let slices = split_onsets(&input_wav_bytes, 0.5).expect("split_onsets function failed");
+
+        assert!(slices.len() > 1, "A drum loop should have several onsets");
+        let total: f32 = slices.iter().map(|slice| len(slice).unwrap()).sum();
+        assert!((total - len(&input_wav_bytes).unwrap()).abs() < 1e-3, "Slices should add up to the input");
+    }
+}
@@ -0,0 +1,203 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use hound::{SampleFormat, WavReader};
+use std::io::Cursor;
+use crate::utils::get_samples;
+use crate::dynamics::{amplitude_to_db, db_to_amplitude};
+use crate::loudness::channel_true_peak;
+
+// Samples at or above this magnitude count as clipped (about -0.01 dBFS)
+const CLIP_THRESHOLD: f32 = 0.999;
+// A 10 ms window is silent when its RMS stays below -60 dBFS
+const SILENCE_WINDOW_MS: f32 = 10.0;
+const SILENCE_THRESHOLD_DB: f32 = -60.0;
+
+#[derive(Clone, Debug)]
+pub struct ChannelStats {
+    pub peak_db: f32,
+    pub true_peak_db: f32,
+    pub rms_db: f32,
+    // Peak to RMS ratio in dB
+    pub crest_db: f32,
+    pub dc_offset: f32,
+    pub clipped: usize,
+    pub silence_percent: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Stats {
+    pub duration: f32,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bits_per_sample: u16,
+    pub float: bool,
+    pub overall: ChannelStats,
+    pub per_channel: Vec<ChannelStats>,
+}
+
+// Figures for the samples of one channel (or of all of them, interleaved)
+fn channel_stats(samples: &[f32], true_peak: f32, window: usize, channels: usize) -> ChannelStats {
+    let count = samples.len().max(1) as f64;
+    let peak = samples.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+    let rms = (samples.iter().map(|&s| (s * s) as f64).sum::<f64>() / count).sqrt() as f32;
+    let dc_offset = (samples.iter().map(|&s| s as f64).sum::<f64>() / count) as f32;
+    let clipped = samples.iter().filter(|s| s.abs() >= CLIP_THRESHOLD).count();
+
+    let windows: Vec<&[f32]> = samples.chunks(window * channels).collect();
+    let silent = windows
+        .iter()
+        .filter(|w| {
+            let mean_square = w.iter().map(|&s| s * s).sum::<f32>() / w.len() as f32;
+            amplitude_to_db(mean_square.sqrt()) < SILENCE_THRESHOLD_DB
+        })
+        .count();
+
+    ChannelStats {
+        peak_db: amplitude_to_db(peak),
+        true_peak_db: amplitude_to_db(true_peak),
+        rms_db: amplitude_to_db(rms),
+        crest_db: if rms > 0.0 { amplitude_to_db(peak / rms) } else { 0.0 },
+        dc_offset,
+        clipped,
+        silence_percent: if windows.is_empty() { 100.0 } else { 100.0 * silent as f32 / windows.len() as f32 },
+    }
+}
+
+pub fn stats(input_wav: &[u8]) -> Result<Stats, String> {
+    // get_samples reports everything as 32-bit float, the stored format comes from the header
+    let original = WavReader::new(Cursor::new(input_wav)).map_err(|e| format!("Invalid WAV: {}", e))?.spec();
+    let (samples, spec) = get_samples(input_wav)?;
+    let channels = spec.channels as usize;
+    let window = ((SILENCE_WINDOW_MS / 1000.0 * spec.sample_rate as f32) as usize).max(1);
+
+    let per_channel: Vec<ChannelStats> = (0..channels)
+        .map(|ch| {
+            let channel: Vec<f32> = samples.iter().skip(ch).step_by(channels).copied().collect();
+            channel_stats(&channel, channel_true_peak(&channel), window, 1)
+        })
+        .collect();
+
+    let true_peak = per_channel.iter().fold(f32::NEG_INFINITY, |acc, c| acc.max(c.true_peak_db));
+    let overall = channel_stats(&samples, db_to_amplitude(true_peak), window, channels);
+
+    Ok(Stats {
+        duration: samples.len() as f32 / channels as f32 / spec.sample_rate as f32,
+        sample_rate: spec.sample_rate,
+        channels: spec.channels,
+        bits_per_sample: original.bits_per_sample,
+        float: original.sample_format == SampleFormat::Float,
+        overall,
+        per_channel,
+    })
+}
+
+// JSON numbers cannot be infinite, digital silence is reported as null
+fn json_db(db: f32) -> String {
+    if db.is_finite() && db > -180.0 { format!("{:.2}", db) } else { "null".to_string() }
+}
+
+fn channel_to_json(c: &ChannelStats) -> String {
+    format!(
+        "{{\"peak_db\":{},\"true_peak_db\":{},\"rms_db\":{},\"crest_db\":{:.2},\"dc_offset\":{:.6},\"clipped\":{},\"silence_percent\":{:.1}}}",
+        json_db(c.peak_db), json_db(c.true_peak_db), json_db(c.rms_db), c.crest_db, c.dc_offset, c.clipped, c.silence_percent
+    )
+}
+
+pub fn stats_to_json(stats: &Stats) -> String {
+    let per_channel: Vec<String> = stats.per_channel.iter().map(channel_to_json).collect();
+    format!(
+        "{{\"duration\":{:.4},\"sample_rate\":{},\"channels\":{},\"bits_per_sample\":{},\"format\":\"{}\",\"overall\":{},\"per_channel\":[{}]}}",
+        stats.duration,
+        stats.sample_rate,
+        stats.channels,
+        stats.bits_per_sample,
+        if stats.float { "float" } else { "int" },
+        channel_to_json(&stats.overall),
+        per_channel.join(",")
+    )
+}
+
+pub fn stats_to_text(stats: &Stats) -> String {
+    let mut text = format!(
+        "Duration: {:.3} s\nFormat: {} Hz, {} channel(s), {}-bit {}\n\n",
+        stats.duration,
+        stats.sample_rate,
+        stats.channels,
+        stats.bits_per_sample,
+        if stats.float { "float" } else { "int" }
+    );
+    text.push_str(&format!(
+        "{:<10}{:>10}{:>10}{:>10}{:>10}{:>11}{:>9}{:>10}\n",
+        "", "Peak", "TruePeak", "RMS", "Crest", "DC", "Clipped", "Silence"
+    ));
+
+    let rows = std::iter::once(("Overall".to_string(), &stats.overall))
+        .chain(stats.per_channel.iter().enumerate().map(|(ch, c)| (format!("Ch {}", ch + 1), c)));
+    for (name, c) in rows {
+        text.push_str(&format!(
+            "{:<10}{:>10.2}{:>10.2}{:>10.2}{:>10.2}{:>11.6}{:>9}{:>9.1}%\n",
+            name, c.peak_db, c.true_peak_db, c.rms_db, c.crest_db, c.dc_offset, c.clipped, c.silence_percent
+        ));
+    }
+    text
+}
+
+#[wasm_bindgen]
+pub fn stats_js(input_wav: &[u8]) -> Result<String, JsValue> {
+    match stats(input_wav) {
+        Ok(stats) => Ok(stats_to_json(&stats)),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Prints level statistics of a WAV file", long_about = None)]
+pub struct StatsArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Print the statistics as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{get_dummy, wrap_samples};
+    use hound::WavSpec;
+
+    #[test]
+    fn test_stats() {
+        let spec = WavSpec { channels: 2, sample_rate: 1000, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        // Left: a clipping square wave with DC, right: half silence
+        let samples: Vec<f32> = (0..1000)
+            .flat_map(|i| [if i % 2 == 0 { 1.0 } else { -0.5 }, if i < 500 { 0.5 } else { 0.0 }])
+            .collect();
+        let wav = wrap_samples(samples, spec).unwrap();
+
+        let stats = stats(&wav).unwrap();
+        assert!((stats.duration - 1.0).abs() < 1e-6);
+        assert_eq!(stats.per_channel.len(), 2);
+
+        let left = &stats.per_channel[0];
+        assert_eq!(left.clipped, 500);
+        assert!((left.dc_offset - 0.25).abs() < 1e-6);
+        assert!(left.peak_db.abs() < 1e-3);
+
+        let right = &stats.per_channel[1];
+        assert!((right.silence_percent - 50.0).abs() < 1e-3);
+        assert!((right.crest_db - amplitude_to_db(2.0f32.sqrt())).abs() < 0.01);
+        assert_eq!(stats.overall.clipped, 500);
+        assert!(stats_to_json(&stats).contains("\"per_channel\":[{\"peak_db\":0.00,"));
+    }
+
+    #[test]
+    fn test_stats_reports_stored_format() {
+        let stats = stats(&get_dummy()).unwrap();
+        assert_eq!(stats.bits_per_sample, 16);
+        assert!(!stats.float);
+        assert!(stats_to_text(&stats).contains("16-bit int"));
+    }
+}
@@ -0,0 +1,151 @@
+use clap::Args;
+use crate::fft::{fft, ifft, Complex};
+use crate::window::Window;
+
+#[derive(Clone, Copy, Debug)]
+pub struct StftSettings {
+    // Power of two
+    pub fft_size: usize,
+    pub hop: usize,
+    pub window: Window,
+}
+
+impl Default for StftSettings {
+    fn default() -> Self {
+        StftSettings { fft_size: 2048, hop: 512, window: Window::Hann }
+    }
+}
+
+impl StftSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.fft_size.is_power_of_two() || self.fft_size < 16 {
+            return Err("FFT size must be a power of two of at least 16.".to_string());
+        }
+        if self.hop == 0 || self.hop > self.fft_size {
+            return Err("Hop size must be between 1 and the FFT size.".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn bins(&self) -> usize {
+        self.fft_size / 2 + 1
+    }
+}
+
+// Short-time Fourier transform of a single channel. Each frame holds the
+// non-negative frequency bins; frame k is centred on sample k * hop.
+pub fn stft(signal: &[f32], settings: &StftSettings) -> Result<Vec<Vec<Complex>>, String> {
+    settings.validate()?;
+    let window = settings.window.coefficients(settings.fft_size);
+    let half = (settings.fft_size / 2) as isize;
+    let frame_count = signal.len().div_ceil(settings.hop) + 1;
+
+    Ok((0..frame_count)
+        .map(|k| {
+            let start = (k * settings.hop) as isize - half;
+            let mut buffer: Vec<Complex> = window
+                .iter()
+                .enumerate()
+                .map(|(i, w)| {
+                    let index = start + i as isize;
+                    let sample = if index >= 0 { signal.get(index as usize).cloned().unwrap_or(0.0) } else { 0.0 };
+                    Complex::new(sample * w, 0.0)
+                })
+                .collect();
+            fft(&mut buffer);
+            buffer.truncate(settings.bins());
+            buffer
+        })
+        .collect())
+}
+
+// Weighted overlap-add resynthesis, the inverse of `stft`
+pub fn istft(frames: &[Vec<Complex>], settings: &StftSettings, output_len: usize) -> Result<Vec<f32>, String> {
+    settings.validate()?;
+    let n = settings.fft_size;
+    let window = settings.window.coefficients(n);
+    let half = (n / 2) as isize;
+
+    let mut output = vec![0.0f32; output_len];
+    let mut weights = vec![0.0f32; output_len];
+
+    for (k, frame) in frames.iter().enumerate() {
+        // Rebuild the full conjugate-symmetric spectrum
+        let mut buffer = vec![Complex::default(); n];
+        for (i, bin) in frame.iter().take(settings.bins()).enumerate() {
+            buffer[i] = *bin;
+            if i > 0 && i < n / 2 {
+                buffer[n - i] = bin.conj();
+            }
+        }
+        ifft(&mut buffer);
+
+        let start = (k * settings.hop) as isize - half;
+        for (i, w) in window.iter().enumerate() {
+            let index = start + i as isize;
+            if index < 0 || index as usize >= output_len {
+                continue;
+            }
+            output[index as usize] += buffer[i].re * w;
+            weights[index as usize] += w * w;
+        }
+    }
+
+    for (sample, weight) in output.iter_mut().zip(weights.iter()) {
+        if *weight > 1e-6 {
+            *sample /= weight;
+        }
+    }
+
+    Ok(output)
+}
+
+// Shared STFT options of the spectral commands
+#[derive(Args, Debug)]
+pub struct StftArgs {
+    /// FFT size (power of two)
+    #[arg(long, default_value_t = 2048)]
+    pub fft_size: usize,
+
+    /// Frames per FFT size (hop = FFT size / overlap)
+    #[arg(long, default_value_t = 4)]
+    pub overlap: usize,
+
+    /// Analysis window (hann, triangle, gaussian, rectangle)
+    #[arg(long, default_value = "hann")]
+    pub window: String,
+}
+
+impl StftArgs {
+    pub fn settings(&self) -> Result<StftSettings, String> {
+        if self.overlap == 0 {
+            return Err("Overlap must be at least 1.".to_string());
+        }
+        Ok(StftSettings { fft_size: self.fft_size, hop: self.fft_size / self.overlap, window: self.window.parse()? })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_stft_roundtrip() {
+        let signal: Vec<f32> = (0..5000).map(|i| (2.0 * PI * 440.0 * i as f32 / 44100.0).sin() * 0.5).collect();
+
+        for settings in [StftSettings::default(), StftSettings { fft_size: 256, hop: 128, window: Window::Triangle }] {
+            let frames = stft(&signal, &settings).unwrap();
+            let restored = istft(&frames, &settings, signal.len()).unwrap();
+            for (a, b) in signal.iter().zip(restored.iter()) {
+                assert!((a - b).abs() < 1e-3, "STFT followed by ISTFT should restore the signal");
+            }
+        }
+    }
+
+    #[test]
+    fn test_stft_rejects_bad_sizes() {
+        let settings = StftSettings { fft_size: 1000, ..Default::default() };
+        assert!(stft(&[0.0; 10], &settings).is_err(), "FFT sizes must be powers of two");
+    }
+}
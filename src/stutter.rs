@@ -0,0 +1,214 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::utils::{get_samples, wrap_samples};
+use crate::time::{note_to_seconds, resolve_time};
+
+// Short fade on every repeat edge to avoid clicks
+const EDGE_FADE_MS: f32 = 2.0;
+
+#[derive(Clone, Debug)]
+pub struct StutterSettings {
+    // Slot size: a note division with `bpm`, otherwise a fraction of the file or seconds
+    pub grid: String,
+    pub bpm: Option<f32>,
+    // Chance of each slot being stuttered
+    pub probability: f32,
+    // How many times a stuttered slot is played (it overwrites the slots that follow)
+    pub repeats: u32,
+    // Gain multiplier applied per repeat
+    pub decay: f32,
+    // Pitch change per repeat in semitones
+    pub pitch_drift: f32,
+    // Fraction of each repeat that is heard (1.0 plays it whole)
+    pub gate: f32,
+}
+
+impl Default for StutterSettings {
+    fn default() -> Self {
+        StutterSettings {
+            grid: "1/16".to_string(),
+            bpm: None,
+            probability: 0.25,
+            repeats: 4,
+            decay: 0.9,
+            pitch_drift: 0.0,
+            gate: 1.0,
+        }
+    }
+}
+
+pub fn stutter(input_wav: &[u8], settings: &StutterSettings, rng: &mut StdRng) -> Result<Vec<u8>, String> {
+    if !(0.0..=1.0).contains(&settings.probability) {
+        return Err("Probability must be between 0.0 and 1.0.".to_string());
+    }
+    if settings.repeats == 0 {
+        return Err("Repeat count must be at least 1.".to_string());
+    }
+    if settings.gate <= 0.0 || settings.gate > 1.0 {
+        return Err("Gate must be above 0.0 and at most 1.0.".to_string());
+    }
+
+    let (samples, spec) = get_samples(input_wav)?;
+    let sample_rate = spec.sample_rate as f32;
+    let channels = spec.channels as usize;
+    let frames = samples.len() / channels;
+
+    let slot_seconds = match settings.bpm {
+        Some(bpm) => note_to_seconds(&settings.grid, bpm)?,
+        None => resolve_time(&settings.grid, frames as f32 / sample_rate)?,
+    };
+    let slot_frames = (slot_seconds * sample_rate).round() as usize;
+    if slot_frames == 0 {
+        return Err("Grid is shorter than a single sample.".to_string());
+    }
+
+    let fade_frames = ((EDGE_FADE_MS / 1000.0 * sample_rate) as usize).max(1);
+    let sounding_frames = ((slot_frames as f32 * settings.gate) as usize).max(1);
+
+    let mut output = samples.clone();
+    let mut slot_start = 0;
+
+    while slot_start < frames {
+        if rng.gen_range(0.0..1.0) >= settings.probability {
+            slot_start += slot_frames;
+            continue;
+        }
+
+        let slice_start = slot_start;
+        for repeat in 0..settings.repeats as usize {
+            let gain = settings.decay.powi(repeat as i32);
+            let rate = 2.0f32.powf(settings.pitch_drift * repeat as f32 / 12.0);
+            let target_start = slot_start + repeat * slot_frames;
+
+            for i in 0..slot_frames {
+                let frame = target_start + i;
+                if frame >= frames {
+                    break;
+                }
+
+                // Read the slice faster or slower, and stay silent where it runs out or is gated
+                let source = i as f32 * rate;
+                let index = source as usize;
+                let audible = i < sounding_frames && index + 1 < slot_frames;
+                let edge = (i.min(sounding_frames.saturating_sub(i + 1)) as f32 / fade_frames as f32).min(1.0);
+
+                for ch in 0..channels {
+                    output[frame * channels + ch] = if audible {
+                        let fraction = source - index as f32;
+                        let a = samples.get((slice_start + index) * channels + ch).cloned().unwrap_or(0.0);
+                        let b = samples.get((slice_start + index + 1) * channels + ch).cloned().unwrap_or(0.0);
+                        (a + (b - a) * fraction) * gain * edge
+                    } else {
+                        0.0
+                    };
+                }
+            }
+        }
+
+        slot_start += slot_frames * settings.repeats as usize;
+    }
+
+    wrap_samples(output, spec)
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn stutter_js(
+    input_wav: &[u8],
+    grid: &str,
+    bpm: Option<f32>,
+    probability: f32,
+    repeats: u32,
+    decay: f32,
+    pitch_drift: f32,
+    gate: f32,
+    seed: u64,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let settings = StutterSettings { grid: grid.to_string(), bpm, probability, repeats, decay, pitch_drift, gate };
+    let mut rng = StdRng::seed_from_u64(seed);
+    match stutter(input_wav, &settings, &mut rng) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Randomly repeats slots of a WAV file on a musical grid", long_about = None)]
+pub struct StutterArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Grid size: a note division with --bpm (e.g., 1/16, 1/8t), otherwise a fraction of the file or seconds
+    #[arg(long, default_value = "1/16")]
+    pub grid: String,
+
+    /// Tempo in BPM
+    #[arg(long)]
+    pub bpm: Option<f32>,
+
+    /// Chance of each slot being stuttered (0.0 to 1.0)
+    #[arg(long, default_value_t = 0.25)]
+    pub probability: f32,
+
+    /// Number of times a stuttered slot is played
+    #[arg(long, default_value_t = 4)]
+    pub repeats: u32,
+
+    /// Gain multiplier per repeat
+    #[arg(long, default_value_t = 0.9)]
+    pub decay: f32,
+
+    /// Pitch change per repeat in semitones
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    pub pitch_drift: f32,
+
+    /// Fraction of each repeat that is heard (0.0 to 1.0)
+    #[arg(long, default_value_t = 1.0)]
+    pub gate: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+
+    #[test]
+    fn test_stutter_repeats_slots() {
+        let input_wav = get_dummy();
+        let settings = StutterSettings { grid: "1/8".to_string(), probability: 1.0, repeats: 2, decay: 1.0, ..Default::default() };
+
+        let output_wav = stutter(&input_wav, &settings, &mut StdRng::seed_from_u64(0)).expect("stutter function failed");
+
+        let (input_samples, spec) = get_samples(&input_wav).unwrap();
+        let (output_samples, _) = get_samples(&output_wav).unwrap();
+        assert_eq!(len(&input_wav).unwrap(), len(&output_wav).unwrap(), "Stutter should not change the duration");
+
+        // The first eighth is played twice, the third eighth twice after it
+        let channels = spec.channels as usize;
+        let slot = input_samples.len() / channels / 8;
+        let i = slot / 2;
+        assert_eq!(output_samples[i * channels], input_samples[i * channels]);
+        assert_eq!(output_samples[(slot + i) * channels], input_samples[i * channels], "The second slot should repeat the first");
+        assert_eq!(output_samples[(3 * slot + i) * channels], input_samples[(2 * slot + i) * channels], "The fourth slot should repeat the third");
+    }
+
+    #[test]
+    fn test_stutter_is_reproducible() {
+        let input_wav = get_dummy();
+        let settings = StutterSettings { grid: "1/16".to_string(), bpm: Some(120.0), gate: 0.5, pitch_drift: -2.0, ..Default::default() };
+
+        let a = stutter(&input_wav, &settings, &mut StdRng::seed_from_u64(4)).unwrap();
+        let b = stutter(&input_wav, &settings, &mut StdRng::seed_from_u64(4)).unwrap();
+
+        assert_eq!(a, b, "The same seed should give the same result");
+        assert_ne!(a, input_wav, "Stutter should modify the audio content");
+    }
+}
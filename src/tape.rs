@@ -0,0 +1,192 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::utils::{get_samples, wrap_samples};
+use crate::delay::DelayLine;
+use crate::dynamics::db_to_amplitude;
+use crate::filter::{Biquad, BiquadType};
+use crate::lfo::{Lfo, Waveform};
+
+// Centre frequency and Q of the low-end resonance of the playback head
+const HEAD_BUMP_HZ: f32 = 90.0;
+const HEAD_BUMP_Q: f32 = 1.0;
+
+#[derive(Clone, Debug)]
+pub struct TapeSettings {
+    // Slow pitch drift: depth in milliseconds of delay modulation, rate in Hz
+    pub wow_depth_ms: f32,
+    pub wow_rate_hz: f32,
+    // Fast pitch jitter
+    pub flutter_depth_ms: f32,
+    pub flutter_rate_hz: f32,
+    // Drive into the saturation stage in dB (0 is nearly clean)
+    pub drive_db: f32,
+    pub head_bump_db: f32,
+    // High-frequency loss of the tape, in Hz
+    pub rolloff_hz: f32,
+    // Level of the tape hiss in dBFS
+    pub hiss_db: f32,
+}
+
+impl Default for TapeSettings {
+    fn default() -> Self {
+        TapeSettings {
+            wow_depth_ms: 1.5,
+            wow_rate_hz: 0.6,
+            flutter_depth_ms: 0.1,
+            flutter_rate_hz: 8.0,
+            drive_db: 6.0,
+            head_bump_db: 3.0,
+            rolloff_hz: 12000.0,
+            hiss_db: -60.0,
+        }
+    }
+}
+
+pub fn tape(input_wav: &[u8], settings: &TapeSettings, rng: &mut StdRng) -> Result<Vec<u8>, String> {
+    if settings.wow_depth_ms < 0.0 || settings.flutter_depth_ms < 0.0 {
+        return Err("Wow and flutter depths cannot be negative.".to_string());
+    }
+
+    let (samples, spec) = get_samples(input_wav)?;
+    let sample_rate = spec.sample_rate as f32;
+    let channels = spec.channels as usize;
+
+    // The read head sits at the centre of the modulation range
+    let centre_ms = settings.wow_depth_ms + settings.flutter_depth_ms;
+    let ms_to_samples = sample_rate / 1000.0;
+    let mut delay_lines: Vec<DelayLine> = (0..channels)
+        .map(|_| DelayLine::new((2.0 * centre_ms * ms_to_samples) as usize + 2))
+        .collect();
+
+    // Both channels share one transport, so one set of LFOs drives them all
+    let mut wow = Lfo::with_phase(Waveform::Sine, settings.wow_rate_hz, sample_rate, rng.gen_range(0.0..1.0));
+    let mut flutter = Lfo::with_phase(Waveform::Sine, settings.flutter_rate_hz, sample_rate, rng.gen_range(0.0..1.0));
+
+    let drive = db_to_amplitude(settings.drive_db);
+    let saturation_gain = drive.tanh();
+
+    let head_bump = Biquad::new(BiquadType::Peak, HEAD_BUMP_HZ, settings.head_bump_db, HEAD_BUMP_Q, sample_rate)?;
+    let rolloff = Biquad::new(BiquadType::Lowpass, settings.rolloff_hz.min(sample_rate * 0.49), 0.0, std::f32::consts::FRAC_1_SQRT_2, sample_rate)?;
+    let mut filters: Vec<(Biquad, Biquad)> = vec![(head_bump, rolloff); channels];
+
+    let hiss = db_to_amplitude(settings.hiss_db);
+    let mut output = Vec::with_capacity(samples.len());
+
+    for frame in samples.chunks(channels) {
+        let modulation_ms = settings.wow_depth_ms * wow.next_value() + settings.flutter_depth_ms * flutter.next_value();
+        let delay_samples = ((centre_ms + modulation_ms) * ms_to_samples).max(1.0);
+
+        for (ch, &sample) in frame.iter().enumerate() {
+            delay_lines[ch].write(sample);
+            let x = delay_lines[ch].read(delay_samples);
+
+            let saturated = (x * drive).tanh() / saturation_gain;
+            let (bump, roll) = &mut filters[ch];
+            let shaped = roll.process(bump.process(saturated));
+
+            let noise = (rng.gen_range(-1.0..1.0) + rng.gen_range(-1.0..1.0)) * 0.5 * hiss;
+            output.push(shaped + noise);
+        }
+    }
+
+    wrap_samples(output, spec)
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn tape_js(
+    input_wav: &[u8],
+    wow_depth_ms: f32,
+    wow_rate_hz: f32,
+    flutter_depth_ms: f32,
+    flutter_rate_hz: f32,
+    drive_db: f32,
+    head_bump_db: f32,
+    rolloff_hz: f32,
+    hiss_db: f32,
+    seed: u64,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let settings = TapeSettings { wow_depth_ms, wow_rate_hz, flutter_depth_ms, flutter_rate_hz, drive_db, head_bump_db, rolloff_hz, hiss_db };
+    let mut rng = StdRng::seed_from_u64(seed);
+    match tape(input_wav, &settings, &mut rng) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Gives a WAV file the character of a tape machine", long_about = None)]
+pub struct TapeArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Wow depth in milliseconds
+    #[arg(long, default_value_t = 1.5)]
+    pub wow: f32,
+
+    /// Wow rate in Hz
+    #[arg(long, default_value_t = 0.6)]
+    pub wow_rate: f32,
+
+    /// Flutter depth in milliseconds
+    #[arg(long, default_value_t = 0.1)]
+    pub flutter: f32,
+
+    /// Flutter rate in Hz
+    #[arg(long, default_value_t = 8.0)]
+    pub flutter_rate: f32,
+
+    /// Saturation drive in dB
+    #[arg(long, default_value_t = 6.0)]
+    pub drive: f32,
+
+    /// Head bump boost around 90 Hz in dB
+    #[arg(long, default_value_t = 3.0, allow_hyphen_values = true)]
+    pub head_bump: f32,
+
+    /// High-frequency rolloff in Hz
+    #[arg(long, default_value_t = 12000.0)]
+    pub rolloff: f32,
+
+    /// Hiss level in dBFS
+    #[arg(long, default_value_t = -60.0, allow_hyphen_values = true)]
+    pub hiss: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+
+    #[test]
+    fn test_tape_effect() {
+        let input_wav = get_dummy();
+
+        let output_wav = tape(&input_wav, &TapeSettings::default(), &mut StdRng::seed_from_u64(1))
+            .expect("tape function failed");
+
+        assert_eq!(len(&input_wav).unwrap(), len(&output_wav).unwrap(), "Tape should not change the duration");
+        assert_ne!(input_wav, output_wav, "Tape should modify the audio content");
+    }
+
+    #[test]
+    fn test_tape_is_reproducible() {
+        let input_wav = get_dummy();
+        let settings = TapeSettings::default();
+
+        let a = tape(&input_wav, &settings, &mut StdRng::seed_from_u64(7)).unwrap();
+        let b = tape(&input_wav, &settings, &mut StdRng::seed_from_u64(7)).unwrap();
+        let c = tape(&input_wav, &settings, &mut StdRng::seed_from_u64(8)).unwrap();
+
+        assert_eq!(a, b, "The same seed should give the same result");
+        assert_ne!(a, c, "Different seeds should give different results");
+    }
+}
@@ -0,0 +1,147 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::{get_samples, wrap_samples};
+use crate::time::resolve_time;
+
+// Inverse of the speed below which the level fades out
+const STANDSTILL_FADE: f32 = 4.0;
+
+pub fn tapestop(
+    input_wav: &[u8],
+    duration_arg: &str,  // Length of the ramp in seconds (can be absolute or fraction like "1/4")
+    curve: f32,          // Shape of the ramp: 1.0 linear, above 1.0 brakes hard, below 1.0 holds then drops
+    spin_up: bool,       // Ramp from standstill up to speed at the start instead of stopping at the end
+) -> Result<Vec<u8>, String> {
+    if curve <= 0.0 {
+        return Err("Curve must be positive.".to_string());
+    }
+
+    let (samples, spec) = get_samples(input_wav)?;
+    let sample_rate = spec.sample_rate as f32;
+    let channels = spec.channels as usize;
+    let frames = samples.len() / channels;
+    let total_duration = frames as f32 / sample_rate;
+
+    let duration = resolve_time(duration_arg, total_duration)?;
+    let ramp_frames = ((duration * sample_rate) as usize).min(frames);
+    if ramp_frames == 0 {
+        return Ok(input_wav.to_vec());
+    }
+
+    // Playback speed for each output frame
+    let ramp_start = if spin_up { 0 } else { frames - ramp_frames };
+    let speed = |frame: usize| -> f32 {
+        if frame < ramp_start {
+            return 1.0;
+        }
+        let progress = ((frame - ramp_start) as f32 / ramp_frames as f32).min(1.0);
+        if spin_up { progress.powf(curve) } else { (1.0 - progress).powf(curve) }
+    };
+
+    let mut output = Vec::with_capacity(samples.len());
+    let mut position = 0.0f64;
+
+    for frame in 0..frames {
+        let index = position.floor() as usize;
+        let fraction = (position - index as f64) as f32;
+
+        // Near standstill the held sample would turn into a DC offset, so the
+        // level follows the speed over the slowest part of the ramp
+        let current_speed = speed(frame);
+        let gain = (current_speed * STANDSTILL_FADE).min(1.0);
+
+        for ch in 0..channels {
+            let current = samples.get(index * channels + ch).cloned().unwrap_or(0.0);
+            let next = samples.get((index + 1) * channels + ch).cloned().unwrap_or(0.0);
+            output.push((current + (next - current) * fraction) * gain);
+        }
+
+        position += current_speed as f64;
+    }
+
+    wrap_samples(output, spec)
+}
+
+#[wasm_bindgen]
+pub fn tapestop_js(
+    input_wav: &[u8],
+    duration: &str,
+    curve: f32,
+    spin_up: bool,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    match tapestop(input_wav, duration, curve, spin_up) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Slows a WAV file down to a stop, or spins it up from standstill", long_about = None)]
+pub struct TapestopArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Length of the ramp in seconds (can be absolute or fraction like "1/4")
+    #[arg(default_value = "1/4")]
+    pub duration: String,
+
+    /// Ramp curve (1.0 linear, above 1.0 brakes hard, below 1.0 holds speed longer)
+    #[arg(long, default_value_t = 1.0)]
+    pub curve: f32,
+
+    /// Spin up from standstill at the start instead of stopping at the end
+    #[arg(long)]
+    pub spin_up: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+
+    #[test]
+    fn test_tapestop_ends_in_silence() {
+        let input_wav = get_dummy();
+
+        let output_wav = tapestop(&input_wav, "1/2", 1.0, false).expect("tapestop function failed");
+
+        let (input_samples, _) = get_samples(&input_wav).unwrap();
+        let (output_samples, _) = get_samples(&output_wav).unwrap();
+        assert_eq!(len(&input_wav).unwrap(), len(&output_wav).unwrap(), "Tape stop should not change the duration");
+        assert_eq!(input_samples[..1000], output_samples[..1000], "Playback should be untouched before the ramp");
+
+        let last = output_samples.len() - 2;
+        assert!(output_samples[last].abs() < 1e-3, "Playback should end in silence, not on a held sample");
+    }
+
+    #[test]
+    fn test_spin_up_starts_still() {
+        let input_wav = get_dummy();
+
+        let output_wav = tapestop(&input_wav, "1.0", 2.0, true).expect("tapestop function failed");
+
+        let (input_samples, spec) = get_samples(&input_wav).unwrap();
+        let (output_samples, _) = get_samples(&output_wav).unwrap();
+        let channels = spec.channels as usize;
+        assert_eq!(output_samples[0], 0.0, "Spin-up should fade in from standstill");
+
+        // A one second squared ramp only covers a third of a second of material,
+        // so full-speed playback afterwards lags the input by two thirds of a second
+        // (plus half a frame, since the ramp is summed one frame at a time)
+        let sample_rate = spec.sample_rate as usize;
+        let frame = 2 * sample_rate;
+        let lagged = frame - sample_rate * 2 / 3;
+        let (a, b) = (input_samples[(lagged - 1) * channels], input_samples[lagged * channels]);
+        let output = output_samples[frame * channels];
+        assert!(
+            output >= a.min(b) && output <= a.max(b),
+            "Playback after the ramp should lag by the distance lost while speeding up"
+        );
+    }
+}
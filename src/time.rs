@@ -32,4 +32,81 @@ pub fn resolve_time(time_str: &str, total_duration: f32) -> Result<f32, String>
     } else {
         Ok(value)
     }
-}
\ No newline at end of file
+}
+
+// Converts a note division such as "1/4", "1/8d" (dotted) or "1/16t" (triplet)
+// into seconds at the given tempo. A whole note lasts four beats.
+pub fn note_to_seconds(note: &str, bpm: f32) -> Result<f32, String> {
+    if bpm <= 0.0 {
+        return Err("BPM must be positive".to_string());
+    }
+
+    let (division, modifier) = match note.chars().last() {
+        Some('d') => (&note[..note.len() - 1], 1.5),
+        Some('t') => (&note[..note.len() - 1], 2.0 / 3.0),
+        _ => (note, 1.0),
+    };
+
+    let (is_fraction, value) = parse_time(division)?;
+    if !is_fraction {
+        return Err(format!("Invalid note division: {}", note));
+    }
+
+    let whole_note = 4.0 * 60.0 / bpm;
+    Ok(whole_note * value * modifier)
+}
+
+// Resolves a time given either as milliseconds ("250") or as a note
+// division ("1/8d") when a tempo is known
+pub fn resolve_ms_or_note(time_str: &str, bpm: Option<f32>) -> Result<f32, String> {
+    if let Ok(ms) = time_str.parse::<f32>() {
+        if ms < 0.0 {
+            return Err("Time cannot be negative".to_string());
+        }
+        return Ok(ms);
+    }
+
+    let bpm = bpm.ok_or_else(|| format!("A BPM is required for note division '{}'", time_str))?;
+    Ok(note_to_seconds(time_str, bpm)? * 1000.0)
+}
+
+// Resolves a modulation rate given either in Hz ("4.5") or as a note
+// division ("1/8t") lasting one cycle when a tempo is known
+pub fn resolve_hz_or_note(rate_str: &str, bpm: Option<f32>) -> Result<f32, String> {
+    if let Ok(hz) = rate_str.parse::<f32>() {
+        if hz <= 0.0 {
+            return Err("Rate must be positive".to_string());
+        }
+        return Ok(hz);
+    }
+
+    let bpm = bpm.ok_or_else(|| format!("A BPM is required for note division '{}'", rate_str))?;
+    Ok(1.0 / note_to_seconds(rate_str, bpm)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_to_seconds() {
+        assert!((note_to_seconds("1/4", 120.0).unwrap() - 0.5).abs() < 1e-6);
+        assert!((note_to_seconds("1/8d", 120.0).unwrap() - 0.375).abs() < 1e-6);
+        assert!((note_to_seconds("1/4t", 120.0).unwrap() - 1.0 / 3.0).abs() < 1e-6);
+        assert!(note_to_seconds("0.5", 120.0).is_err(), "Plain numbers are not note divisions");
+    }
+
+    #[test]
+    fn test_resolve_ms_or_note() {
+        assert_eq!(resolve_ms_or_note("250", None).unwrap(), 250.0);
+        assert!((resolve_ms_or_note("1/16", Some(120.0)).unwrap() - 125.0).abs() < 1e-3);
+        assert!(resolve_ms_or_note("1/16", None).is_err(), "Note divisions need a BPM");
+    }
+
+    #[test]
+    fn test_resolve_hz_or_note() {
+        assert_eq!(resolve_hz_or_note("4.5", None).unwrap(), 4.5);
+        assert!((resolve_hz_or_note("1/8", Some(120.0)).unwrap() - 4.0).abs() < 1e-4);
+        assert!(resolve_hz_or_note("0", None).is_err(), "A zero rate should be rejected");
+    }
+}
@@ -0,0 +1,184 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::{get_samples, wrap_samples};
+use crate::dynamics::{amplitude_to_db, db_to_amplitude, EnvelopeFollower};
+
+// The fast follower tracks hits, the slow one the body of the sound. Where the
+// fast one is above, we are in an attack; where it is below, in a sustain.
+const FAST_ATTACK_MS: f32 = 1.0;
+const FAST_RELEASE_MS: f32 = 50.0;
+const SLOW_ATTACK_MS: f32 = 25.0;
+const SLOW_RELEASE_MS: f32 = 250.0;
+// Follower difference at which the full attack/sustain gain is applied (at sensitivity 1)
+const FULL_SCALE_DB: f32 = 12.0;
+const LEVEL_FLOOR: f32 = 1e-6;
+
+#[derive(Clone, Debug)]
+pub struct TransientSettings {
+    pub attack_db: f32,
+    pub sustain_db: f32,
+    pub sensitivity: f32,
+    pub output_db: f32,
+    pub stereo_link: bool,
+}
+
+impl Default for TransientSettings {
+    fn default() -> Self {
+        TransientSettings {
+            attack_db: 6.0,
+            sustain_db: 0.0,
+            sensitivity: 1.0,
+            output_db: 0.0,
+            stereo_link: true,
+        }
+    }
+}
+
+struct TransientState {
+    fast: EnvelopeFollower,
+    slow: EnvelopeFollower,
+    gain: f32,
+}
+
+impl TransientState {
+    fn new(sample_rate: f32) -> Self {
+        TransientState {
+            fast: EnvelopeFollower::new(FAST_ATTACK_MS, FAST_RELEASE_MS, sample_rate),
+            slow: EnvelopeFollower::new(SLOW_ATTACK_MS, SLOW_RELEASE_MS, sample_rate),
+            gain: 1.0,
+        }
+    }
+
+    fn process(&mut self, level: f32, settings: &TransientSettings) {
+        let fast = self.fast.process(level).max(LEVEL_FLOOR);
+        let slow = self.slow.process(level).max(LEVEL_FLOOR);
+        let difference = (amplitude_to_db(fast) - amplitude_to_db(slow)) * settings.sensitivity / FULL_SCALE_DB;
+
+        let gain_db = if difference > 0.0 {
+            settings.attack_db * difference.min(1.0)
+        } else {
+            settings.sustain_db * (-difference).min(1.0)
+        };
+        self.gain = db_to_amplitude(gain_db);
+    }
+}
+
+pub fn transient(input_wav: &[u8], settings: &TransientSettings) -> Result<Vec<u8>, String> {
+    if settings.sensitivity < 0.0 {
+        return Err("Sensitivity cannot be negative.".to_string());
+    }
+
+    let (mut samples, spec) = get_samples(input_wav)?;
+    let sample_rate = spec.sample_rate as f32;
+    let channels = spec.channels as usize;
+    let output_gain = db_to_amplitude(settings.output_db);
+
+    let paths = if settings.stereo_link { 1 } else { channels };
+    let mut states: Vec<TransientState> = (0..paths).map(|_| TransientState::new(sample_rate)).collect();
+
+    for frame in samples.chunks_mut(channels) {
+        if settings.stereo_link {
+            let level = frame.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+            states[0].process(level, settings);
+        } else {
+            for (state, sample) in states.iter_mut().zip(frame.iter()) {
+                state.process(sample.abs(), settings);
+            }
+        }
+
+        for (ch, sample) in frame.iter_mut().enumerate() {
+            *sample *= states[if settings.stereo_link { 0 } else { ch }].gain * output_gain;
+        }
+    }
+
+    wrap_samples(samples, spec)
+}
+
+#[wasm_bindgen]
+pub fn transient_js(
+    input_wav: &[u8],
+    attack_db: f32,
+    sustain_db: f32,
+    sensitivity: f32,
+    output_db: f32,
+    stereo_link: bool,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let settings = TransientSettings { attack_db, sustain_db, sensitivity, output_db, stereo_link };
+    match transient(input_wav, &settings) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Shapes the attack and sustain of a WAV file", long_about = None)]
+pub struct TransientArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Gain applied to attacks in dB
+    #[arg(long, default_value_t = 6.0, allow_hyphen_values = true)]
+    pub attack: f32,
+
+    /// Gain applied to sustains in dB
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    pub sustain: f32,
+
+    /// How strongly attacks and sustains are detected
+    #[arg(long, default_value_t = 1.0)]
+    pub sensitivity: f32,
+
+    /// Output gain in dB
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    pub output_gain: f32,
+
+    /// Process channels independently instead of stereo-linked
+    #[arg(long)]
+    pub unlinked: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hound::{WavSpec, SampleFormat};
+
+    fn burst() -> Vec<u8> {
+        let spec = WavSpec { channels: 1, sample_rate: 44100, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        // Silence, then a held square wave
+        let mut samples = vec![0.0f32; 4410];
+        samples.extend((0..22050).map(|i| if (i / 50) % 2 == 0 { 0.25 } else { -0.25 }));
+        wrap_samples(samples, spec).unwrap()
+    }
+
+    #[test]
+    fn test_attack_is_boosted() {
+        let input_wav = burst();
+        let output_wav = transient(&input_wav, &TransientSettings::default()).expect("transient function failed");
+        let (output, _) = get_samples(&output_wav).unwrap();
+
+        assert!(output[4410 + 200].abs() > 0.25 * 1.5, "The onset should be boosted");
+        assert!((output[4410 + 20000].abs() - 0.25).abs() < 0.01, "The held part should be left alone");
+    }
+
+    #[test]
+    fn test_sustain_is_cut() {
+        let spec = WavSpec { channels: 1, sample_rate: 44100, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        // A hit decaying over about 50 ms
+        let samples: Vec<f32> = (0..22050)
+            .map(|i| (if (i / 50) % 2 == 0 { 0.8 } else { -0.8 }) * (-(i as f32) / 2205.0).exp())
+            .collect();
+        let input_wav = wrap_samples(samples.clone(), spec).unwrap();
+
+        let settings = TransientSettings { attack_db: 0.0, sustain_db: -12.0, stereo_link: false, ..Default::default() };
+        let output_wav = transient(&input_wav, &settings).expect("transient function failed");
+        let (output, _) = get_samples(&output_wav).unwrap();
+
+        assert!(output[8820].abs() < samples[8820].abs() * 0.7, "The tail should be cut");
+        assert!(transient(&input_wav, &TransientSettings { sensitivity: -1.0, ..Default::default() }).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::{get_samples, wrap_samples};
+use crate::lfo::{Lfo, Waveform};
+use crate::time::resolve_hz_or_note;
+
+pub fn tremolo(
+    input_wav: &[u8],
+    rate_hz: f32,        // LFO rate in Hz
+    depth: f32,          // Modulation depth (0.0 none to 1.0 full silence at the trough)
+    waveform: Waveform,  // LFO shape
+) -> Result<Vec<u8>, String> {
+    if !(0.0..=1.0).contains(&depth) {
+        return Err("Depth must be between 0.0 and 1.0.".to_string());
+    }
+
+    let (mut samples, spec) = get_samples(input_wav)?;
+    let channels = spec.channels as usize;
+
+    // A single LFO for all channels keeps the image centred
+    let mut lfo = Lfo::new(waveform, rate_hz, spec.sample_rate as f32);
+
+    for frame in samples.chunks_mut(channels) {
+        let gain = 1.0 - depth * (1.0 - lfo.next_unipolar());
+        for sample in frame.iter_mut() {
+            *sample *= gain;
+        }
+    }
+
+    wrap_samples(samples, spec)
+}
+
+#[wasm_bindgen]
+pub fn tremolo_js(
+    input_wav: &[u8],
+    rate: &str,
+    bpm: Option<f32>,
+    depth: f32,
+    waveform: &str,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let result = resolve_hz_or_note(rate, bpm).and_then(|rate_hz| {
+        let waveform = waveform.parse::<Waveform>()?;
+        tremolo(input_wav, rate_hz, depth, waveform)
+    });
+    match result {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Applies a (tempo-synced) tremolo to a WAV file", long_about = None)]
+pub struct TremoloArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// LFO rate in Hz (e.g., 5) or note division per cycle with --bpm (e.g., 1/8, 1/8d, 1/16t)
+    #[arg(long, default_value = "5")]
+    pub rate: String,
+
+    /// Tempo in BPM, needed when the rate is a note division
+    #[arg(long)]
+    pub bpm: Option<f32>,
+
+    /// Modulation depth (0.0 to 1.0)
+    #[arg(long, default_value_t = 0.5)]
+    pub depth: f32,
+
+    /// LFO waveform (sine, triangle, square, saw, random)
+    #[arg(long, default_value = "sine")]
+    pub waveform: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+
+    #[test]
+    fn test_tremolo_effect() {
+        let input_wav = get_dummy();
+
+        let output_wav = tremolo(&input_wav, 4.0, 0.8, Waveform::Square).expect("tremolo function failed");
+
+        assert_eq!(len(&input_wav).unwrap(), len(&output_wav).unwrap(), "Tremolo should not change the duration");
+        assert_ne!(input_wav, output_wav, "Tremolo should modify the audio content");
+    }
+
+    #[test]
+    fn test_tremolo_zero_depth_is_transparent() {
+        let input_wav = get_dummy();
+
+        let output_wav = tremolo(&input_wav, 4.0, 0.0, Waveform::Sine).unwrap();
+
+        let (input_samples, _) = get_samples(&input_wav).unwrap();
+        let (output_samples, _) = get_samples(&output_wav).unwrap();
+        for (a, b) in input_samples.iter().zip(output_samples.iter()) {
+            assert!((a - b).abs() < 1e-6, "Zero depth should leave the signal untouched");
+        }
+    }
+}
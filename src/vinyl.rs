@@ -0,0 +1,202 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::utils::{get_samples, wrap_samples};
+use crate::dynamics::db_to_amplitude;
+use crate::filter::{Biquad, BiquadType, OnePoleLowpass};
+
+// Peak levels and per-sample decay of the two kinds of surface noise:
+// crackle is a dense bed of tiny ticks, pops are rare and much louder thumps
+const CRACKLE_LEVEL: f32 = 0.04;
+const CRACKLE_DECAY: f32 = 0.4;
+const POP_LEVEL: f32 = 0.35;
+const POP_DECAY: f32 = 0.97;
+const POP_TONE_HZ: f32 = 2500.0;
+// Rumble is low-passed noise from the turntable motor and bearings
+const RUMBLE_HZ: f32 = 30.0;
+// Highest high cut as a fraction of the sample rate, safely below Nyquist
+const MAX_HIGHCUT_RATIO: f32 = 0.45;
+
+#[derive(Clone, Debug)]
+pub struct VinylSettings {
+    // Average number of crackles per second
+    pub crackle_density: f32,
+    // Average number of pops per second
+    pub pop_rate: f32,
+    // Level of the rumble in dBFS
+    pub rumble_db: f32,
+    // Band limits of the playback chain, in Hz
+    pub lowcut_hz: f32,
+    pub highcut_hz: f32,
+}
+
+impl Default for VinylSettings {
+    fn default() -> Self {
+        VinylSettings {
+            crackle_density: 40.0,
+            pop_rate: 0.5,
+            rumble_db: -45.0,
+            lowcut_hz: 40.0,
+            highcut_hz: 9000.0,
+        }
+    }
+}
+
+// A decaying impulse, retriggered at random
+struct Impulse {
+    value: f32,
+    decay: f32,
+}
+
+impl Impulse {
+    fn new(decay: f32) -> Self {
+        Impulse { value: 0.0, decay }
+    }
+
+    fn next(&mut self, rng: &mut StdRng, probability: f32, level: f32) -> f32 {
+        if rng.gen_range(0.0..1.0) < probability {
+            self.value = rng.gen_range(-level..=level);
+        }
+        let out = self.value;
+        self.value *= self.decay;
+        out
+    }
+}
+
+pub fn vinyl(input_wav: &[u8], settings: &VinylSettings, rng: &mut StdRng) -> Result<Vec<u8>, String> {
+    if settings.crackle_density < 0.0 || settings.pop_rate < 0.0 {
+        return Err("Crackle density and pop rate cannot be negative.".to_string());
+    }
+
+    let (samples, spec) = get_samples(input_wav)?;
+    let sample_rate = spec.sample_rate as f32;
+    let channels = spec.channels as usize;
+
+    // Low sample rates cannot hold a high cut near the top of the audio band
+    let highcut_hz = settings.highcut_hz.min(sample_rate * MAX_HIGHCUT_RATIO);
+    if settings.lowcut_hz >= highcut_hz {
+        return Err("Low cut must be below high cut.".to_string());
+    }
+
+    let q = std::f32::consts::FRAC_1_SQRT_2;
+    let lowcut = Biquad::new(BiquadType::Highpass, settings.lowcut_hz, 0.0, q, sample_rate)?;
+    let highcut = Biquad::new(BiquadType::Lowpass, highcut_hz, 0.0, q, sample_rate)?;
+    let mut filters: Vec<(Biquad, Biquad)> = vec![(lowcut, highcut); channels];
+
+    let mut crackle = Impulse::new(CRACKLE_DECAY);
+    let mut pop = Impulse::new(POP_DECAY);
+    let mut pop_tone = OnePoleLowpass::new(POP_TONE_HZ, sample_rate);
+    let mut rumble = OnePoleLowpass::new(RUMBLE_HZ, sample_rate);
+
+    // The one-pole loses most of the noise energy, so the rumble is scaled
+    // back up by roughly the ratio of the bandwidths
+    let rumble_level = db_to_amplitude(settings.rumble_db) * (sample_rate / (2.0 * RUMBLE_HZ)).sqrt();
+    let crackle_probability = settings.crackle_density / sample_rate;
+    let pop_probability = settings.pop_rate / sample_rate;
+
+    let mut output = Vec::with_capacity(samples.len());
+
+    for frame in samples.chunks(channels) {
+        // Surface noise is the same on both channels
+        let noise = crackle.next(rng, crackle_probability, CRACKLE_LEVEL)
+            + pop_tone.process(pop.next(rng, pop_probability, POP_LEVEL))
+            + rumble.process(rng.gen_range(-1.0..1.0)) * rumble_level;
+
+        for (ch, &sample) in frame.iter().enumerate() {
+            let (low, high) = &mut filters[ch];
+            output.push(high.process(low.process(sample)) + noise);
+        }
+    }
+
+    wrap_samples(output, spec)
+}
+
+#[wasm_bindgen]
+pub fn vinyl_js(
+    input_wav: &[u8],
+    crackle_density: f32,
+    pop_rate: f32,
+    rumble_db: f32,
+    lowcut_hz: f32,
+    highcut_hz: f32,
+    seed: u64,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    let settings = VinylSettings { crackle_density, pop_rate, rumble_db, lowcut_hz, highcut_hz };
+    let mut rng = StdRng::seed_from_u64(seed);
+    match vinyl(input_wav, &settings, &mut rng) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Makes a WAV file sound like it is played from a record", long_about = None)]
+pub struct VinylArgs {
+    /// Input WAV file
+    #[arg()]
+    pub input: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Average number of crackles per second
+    #[arg(long, default_value_t = 40.0)]
+    pub crackle: f32,
+
+    /// Average number of pops per second
+    #[arg(long, default_value_t = 0.5)]
+    pub pops: f32,
+
+    /// Rumble level in dBFS
+    #[arg(long, default_value_t = -45.0, allow_hyphen_values = true)]
+    pub rumble: f32,
+
+    /// Low cut frequency in Hz
+    #[arg(long, default_value_t = 40.0)]
+    pub lowcut: f32,
+
+    /// High cut frequency in Hz
+    #[arg(long, default_value_t = 9000.0)]
+    pub highcut: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::len::len;
+    use crate::silence::silence;
+
+    #[test]
+    fn test_vinyl_adds_surface_noise() {
+        let input_wav = silence(1.0).unwrap();
+
+        let output_wav = vinyl(&input_wav, &VinylSettings::default(), &mut StdRng::seed_from_u64(1))
+            .expect("vinyl function failed");
+
+        let (output, _) = get_samples(&output_wav).unwrap();
+        assert_eq!(len(&input_wav).unwrap(), len(&output_wav).unwrap(), "Vinyl should not change the duration");
+        assert!(output.iter().any(|s| s.abs() > 1e-3), "Silence should pick up crackle and rumble");
+    }
+
+    #[test]
+    fn test_vinyl_clamps_highcut_at_low_sample_rates() {
+        let spec = hound::WavSpec { channels: 1, sample_rate: 22050, bits_per_sample: 32, sample_format: hound::SampleFormat::Float };
+        let input_wav = wrap_samples(vec![0.0; 22050], spec).unwrap();
+        let settings = VinylSettings { highcut_hz: 12000.0, ..VinylSettings::default() };
+
+        assert!(vinyl(&input_wav, &settings, &mut StdRng::seed_from_u64(1)).is_ok(), "A high cut above Nyquist should be clamped");
+    }
+
+    #[test]
+    fn test_vinyl_is_reproducible() {
+        let input_wav = silence(0.5).unwrap();
+        let settings = VinylSettings::default();
+
+        let a = vinyl(&input_wav, &settings, &mut StdRng::seed_from_u64(3)).unwrap();
+        let b = vinyl(&input_wav, &settings, &mut StdRng::seed_from_u64(3)).unwrap();
+
+        assert_eq!(a, b, "The same seed should give the same result");
+    }
+}
@@ -0,0 +1,173 @@
+use wasm_bindgen::prelude::*;
+use clap::Parser;
+use crate::utils::{get_samples, wrap_samples};
+use crate::filter::{Biquad, BiquadType};
+use crate::dynamics::EnvelopeFollower;
+
+// Range covered by the filterbank (the top is lowered for low sample rates)
+const LOWEST_BAND_HZ: f32 = 80.0;
+const HIGHEST_BAND_HZ: f32 = 8000.0;
+
+// Log-spaced centre frequencies and the Q that makes neighbouring bands meet
+fn filterbank(bands: usize, sample_rate: f32) -> (Vec<f32>, f32) {
+    let highest = HIGHEST_BAND_HZ.min(sample_rate * 0.4);
+    let octaves = (highest / LOWEST_BAND_HZ).log2();
+    let frequencies = (0..bands)
+        .map(|b| LOWEST_BAND_HZ * 2.0f32.powf(octaves * (b as f32 + 0.5) / bands as f32))
+        .collect();
+
+    let bandwidth = 2.0f32.powf(octaves / bands as f32);
+    let q = bandwidth.sqrt() / (bandwidth - 1.0);
+    (frequencies, q)
+}
+
+pub fn vocoder(
+    carrier_wav: &[u8],    // Sound that is played (e.g. a synth pad)
+    modulator_wav: &[u8],  // Sound whose spectral envelope is imposed (e.g. a voice or drums)
+    bands: usize,          // Number of filterbank bands
+    attack_ms: f32,        // Envelope follower attack
+    release_ms: f32,       // Envelope follower release
+    formant_shift: f32,    // Shift of the carrier bands against the analysis bands, in semitones
+) -> Result<Vec<u8>, String> {
+    if !(2..=64).contains(&bands) {
+        return Err("Band count must be between 2 and 64.".to_string());
+    }
+
+    let (carrier, spec) = get_samples(carrier_wav)?;
+    let (modulator, modulator_spec) = get_samples(modulator_wav)?;
+    if spec.sample_rate != modulator_spec.sample_rate {
+        return Err("Sample rates do not match.".to_string());
+    }
+
+    let sample_rate = spec.sample_rate as f32;
+    let channels = spec.channels as usize;
+    let modulator_channels = modulator_spec.channels as usize;
+    let carrier_frames = carrier.len() / channels;
+    if carrier_frames == 0 {
+        return Err("Carrier is empty.".to_string());
+    }
+
+    let (frequencies, q) = filterbank(bands, sample_rate);
+    let shift = 2.0f32.powf(formant_shift / 12.0);
+
+    let mut analysis = Vec::with_capacity(bands);
+    let mut synthesis = Vec::with_capacity(bands);
+    for &frequency in &frequencies {
+        analysis.push((
+            Biquad::new(BiquadType::Bandpass, frequency, 0.0, q, sample_rate)?,
+            EnvelopeFollower::new(attack_ms, release_ms, sample_rate),
+        ));
+        let shifted = (frequency * shift).clamp(20.0, sample_rate * 0.45);
+        synthesis.push(vec![Biquad::new(BiquadType::Bandpass, shifted, 0.0, q, sample_rate)?; channels]);
+    }
+
+    // The output follows the modulator, looping the carrier if it is shorter
+    let mut output = Vec::with_capacity(modulator.len() / modulator_channels * channels);
+    let mut envelopes = vec![0.0f32; bands];
+
+    for (i, modulator_frame) in modulator.chunks(modulator_channels).enumerate() {
+        let key = modulator_frame.iter().sum::<f32>() / modulator_channels as f32;
+        for ((filter, follower), envelope) in analysis.iter_mut().zip(envelopes.iter_mut()) {
+            *envelope = follower.process(filter.process(key).abs());
+        }
+
+        let carrier_frame = &carrier[(i % carrier_frames) * channels..][..channels];
+        for (ch, &sample) in carrier_frame.iter().enumerate() {
+            let value: f32 = synthesis
+                .iter_mut()
+                .zip(envelopes.iter())
+                .map(|(filters, envelope)| filters[ch].process(sample) * envelope)
+                .sum();
+            output.push(value);
+        }
+    }
+
+    // Match the peak level of the modulator
+    let modulator_peak = modulator.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+    let output_peak = output.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+    if output_peak > 0.0 {
+        let scale = modulator_peak / output_peak;
+        output.iter_mut().for_each(|s| *s *= scale);
+    }
+
+    wrap_samples(output, spec)
+}
+
+#[wasm_bindgen]
+pub fn vocoder_js(
+    carrier_wav: &[u8],
+    modulator_wav: &[u8],
+    bands: usize,
+    attack_ms: f32,
+    release_ms: f32,
+    formant_shift: f32,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    match vocoder(carrier_wav, modulator_wav, bands, attack_ms, release_ms, formant_shift) {
+        Ok(result_vec) => Ok(js_sys::Uint8Array::from(result_vec.as_slice())),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Vocodes a carrier WAV with the spectral envelope of a modulator WAV", long_about = None)]
+pub struct VocodeArgs {
+    /// Carrier WAV file (the sound that is heard)
+    #[arg()]
+    pub carrier: String,
+
+    /// Modulator WAV file (the sound that shapes it)
+    #[arg()]
+    pub modulator: String,
+
+    /// Output WAV file
+    #[arg()]
+    pub output: String,
+
+    /// Number of bands
+    #[arg(long, default_value_t = 16)]
+    pub bands: usize,
+
+    /// Envelope attack in milliseconds
+    #[arg(long, default_value_t = 5.0)]
+    pub attack: f32,
+
+    /// Envelope release in milliseconds
+    #[arg(long, default_value_t = 50.0)]
+    pub release: f32,
+
+    /// Formant shift in semitones
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    pub formant: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cut::cut;
+    use crate::len::len;
+    use crate::utils::get_dummy;
+    use crate::silence::silence;
+
+    #[test]
+    fn test_vocoder_follows_modulator() {
+        let carrier = cut(&get_dummy(), "0", "1").unwrap();
+        let modulator = cut(&get_dummy(), "1", "1.5").unwrap();
+
+        let output_wav = vocoder(&carrier, &modulator, 16, 5.0, 50.0, 2.0).expect("vocoder function failed");
+
+        assert!((len(&output_wav).unwrap() - len(&modulator).unwrap()).abs() < 1e-3, "Output should last as long as the modulator");
+        let (output, _) = get_samples(&output_wav).unwrap();
+        assert!(output.iter().any(|s| s.abs() > 1e-3), "Vocoded output should be audible");
+    }
+
+    #[test]
+    fn test_silent_modulator_gives_silence() {
+        let carrier = cut(&get_dummy(), "0", "0.5").unwrap();
+        let modulator = silence(0.5).unwrap();
+
+        let output_wav = vocoder(&carrier, &modulator, 8, 5.0, 50.0, 0.0).unwrap();
+        let (output, _) = get_samples(&output_wav).unwrap();
+        assert!(output.iter().all(|s| *s == 0.0), "A silent modulator should silence the carrier");
+        assert!(vocoder(&carrier, &modulator, 1, 5.0, 50.0, 0.0).is_err(), "A single band should be rejected");
+    }
+}
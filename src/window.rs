@@ -0,0 +1,70 @@
+use std::f32::consts::PI;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Window {
+    Hann,
+    Triangle,
+    Gaussian,
+    Rectangle,
+}
+
+impl FromStr for Window {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hann" => Ok(Window::Hann),
+            "triangle" => Ok(Window::Triangle),
+            "gaussian" => Ok(Window::Gaussian),
+            "rectangle" => Ok(Window::Rectangle),
+            _ => Err(format!("Window not recognized: {}", s)),
+        }
+    }
+}
+
+pub fn get_window_list() -> Vec<String> {
+    ["hann", "triangle", "gaussian", "rectangle"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+impl Window {
+    // Value at `position` from 0.0 to 1.0 across the window
+    pub fn value(&self, position: f32) -> f32 {
+        match self {
+            Window::Hann => 0.5 - 0.5 * (2.0 * PI * position).cos(),
+            Window::Triangle => 1.0 - (2.0 * position - 1.0).abs(),
+            Window::Gaussian => (-0.5 * ((position - 0.5) / 0.15).powi(2)).exp(),
+            Window::Rectangle => 1.0,
+        }
+    }
+
+    // Periodic window of `size` samples, as used for overlap-add
+    pub fn coefficients(&self, size: usize) -> Vec<f32> {
+        (0..size).map(|i| self.value(i as f32 / size as f32)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_peak_in_the_middle() {
+        for name in get_window_list() {
+            let window: Window = name.parse().unwrap();
+            assert!((window.value(0.5) - 1.0).abs() < 1e-6, "{} should peak at 1.0", name);
+            assert!(window.value(0.1) <= window.value(0.4), "{} should rise towards the middle", name);
+        }
+    }
+
+    #[test]
+    fn test_hann_overlap_adds_to_one() {
+        let coefficients = Window::Hann.coefficients(64);
+        for i in 0..32 {
+            assert!((coefficients[i] + coefficients[i + 32] - 1.0).abs() < 1e-5, "Hann at 50% overlap should sum to one");
+        }
+    }
+}